@@ -0,0 +1,243 @@
+use std::{fmt::Display, num::ParseIntError};
+
+#[derive(Debug, PartialEq)]
+pub enum ListError {
+    Columns { line: usize, found: usize },
+    ParseInt { line: usize, err: ParseIntError },
+    LengthMismatch,
+    IdOverflow { list: &'static str, index: usize },
+    Overflow { statistic: &'static str },
+}
+
+impl Display for ListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListError::Columns { line, found } => {
+                write!(f, "line {}: expected 2 columns, found {}", line, found)
+            }
+            ListError::ParseInt { line, err } => {
+                write!(f, "line {}: invalid number: {}", line, err)
+            }
+            ListError::LengthMismatch => write!(f, "lists have different lengths"),
+            ListError::IdOverflow { list, index } => {
+                write!(f, "{} list: ID #{} does not fit in an i64", list, index + 1)
+            }
+            ListError::Overflow { statistic } => write!(f, "{} overflows", statistic),
+        }
+    }
+}
+
+impl std::error::Error for ListError {}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub distance: u64,
+    pub similarity: i64,
+    /// Lower median of the gaps between paired IDs, `None` for empty lists.
+    pub median_gap: Option<u64>,
+    /// IDs appearing the most times across both lists, with that count.
+    pub most_repeated: (usize, Vec<i64>),
+    pub left_only: Vec<i64>,
+    pub right_only: Vec<i64>,
+}
+
+/// Computes every statistic over the two lists at once: both are sorted, then
+/// walked together one run of equal IDs at a time. IDs that do not fit in an
+/// `i64` are reported with their position in their list.
+pub fn statistics<L, R>(left: L, right: R) -> Result<Statistics, ListError>
+where
+    L: IntoIterator,
+    L::Item: TryInto<i64>,
+    R: IntoIterator,
+    R::Item: TryInto<i64>,
+{
+    let mut left = to_ids(left, "left")?;
+    let mut right = to_ids(right, "right")?;
+    if left.len() != right.len() {
+        return Err(ListError::LengthMismatch);
+    }
+    left.sort();
+    right.sort();
+
+    let mut stats = Statistics::default();
+    let mut gaps: Vec<u64> = left
+        .iter()
+        .zip(&right)
+        .map(|(l, r)| l.abs_diff(*r))
+        .collect();
+    for gap in &gaps {
+        stats.distance = stats
+            .distance
+            .checked_add(*gap)
+            .ok_or(ListError::Overflow {
+                statistic: "distance",
+            })?;
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        let id = match (left.get(i), right.get(j)) {
+            (Some(&l), Some(&r)) => l.min(r),
+            (Some(&l), None) => l,
+            (None, Some(&r)) => r,
+            (None, None) => unreachable!(),
+        };
+        let nb_left = left[i..].iter().take_while(|&&x| x == id).count();
+        let nb_right = right[j..].iter().take_while(|&&x| x == id).count();
+
+        stats.similarity = id
+            .checked_mul((nb_left * nb_right) as i64)
+            .and_then(|s| stats.similarity.checked_add(s))
+            .ok_or(ListError::Overflow {
+                statistic: "similarity",
+            })?;
+
+        match (nb_left, nb_right) {
+            (_, 0) => stats.left_only.push(id),
+            (0, _) => stats.right_only.push(id),
+            _ => (),
+        }
+
+        let count = nb_left + nb_right;
+        if count > stats.most_repeated.0 {
+            stats.most_repeated = (count, vec![id]);
+        } else if count == stats.most_repeated.0 {
+            stats.most_repeated.1.push(id);
+        }
+
+        i += nb_left;
+        j += nb_right;
+    }
+
+    if !gaps.is_empty() {
+        let middle = (gaps.len() - 1) / 2;
+        stats.median_gap = Some(*gaps.select_nth_unstable(middle).1);
+    }
+
+    Ok(stats)
+}
+
+fn to_ids<I>(ids: I, list: &'static str) -> Result<Vec<i64>, ListError>
+where
+    I: IntoIterator,
+    I::Item: TryInto<i64>,
+{
+    ids.into_iter()
+        .enumerate()
+        .map(|(index, id)| {
+            id.try_into()
+                .map_err(|_| ListError::IdOverflow { list, index })
+        })
+        .collect()
+}
+
+pub fn parse_lists(input: &str) -> Result<(Vec<i64>, Vec<i64>), ListError> {
+    let mut left = vec![];
+    let mut right = vec![];
+
+    for (i, l) in input.lines().enumerate() {
+        let columns: Vec<&str> = l.split_whitespace().collect();
+        let [a, b] = columns[..] else {
+            return Err(ListError::Columns {
+                line: i + 1,
+                found: columns.len(),
+            });
+        };
+        let parse = |n: &str| {
+            n.parse::<i64>()
+                .map_err(|err| ListError::ParseInt { line: i + 1, err })
+        };
+        left.push(parse(a)?);
+        right.push(parse(b)?);
+    }
+
+    Ok((left, right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = r#"3   4
+4   3
+2   5
+1   3
+3   9
+3   3"#;
+        let (left, right) = parse_lists(input).unwrap();
+        assert_eq!(
+            statistics(left, right),
+            Ok(Statistics {
+                distance: 11,
+                similarity: 31,
+                median_gap: Some(1),
+                most_repeated: (6, vec![3]),
+                left_only: vec![1, 2],
+                right_only: vec![5, 9],
+            })
+        );
+    }
+
+    #[test]
+    fn any_integers() {
+        let stats = statistics([1u8, 2, 2], vec![2i32, 2, 7]).unwrap();
+        assert_eq!(stats.distance, 6);
+        assert_eq!(stats.similarity, 8);
+        assert_eq!(stats.most_repeated, (4, vec![2]));
+
+        let stats = statistics([i64::MIN], [i64::MAX]).unwrap();
+        assert_eq!(stats.distance, u64::MAX);
+        assert_eq!(
+            statistics([i64::MIN, 0], [i64::MAX, i64::MAX]),
+            Err(ListError::Overflow {
+                statistic: "distance"
+            })
+        );
+        assert_eq!(statistics([1], [1, 2]), Err(ListError::LengthMismatch));
+    }
+
+    #[test]
+    fn wide_integers() {
+        let stats = statistics([3u64, 1], vec![2usize, 2]).unwrap();
+        assert_eq!(stats.distance, 2);
+        assert_eq!(statistics([1i128], [2i128]).unwrap().distance, 1);
+
+        assert_eq!(
+            statistics([u64::MAX], [0u64]),
+            Err(ListError::IdOverflow {
+                list: "left",
+                index: 0
+            })
+        );
+        let err = statistics([0i128, 1], [1, i128::from(i64::MIN) - 1]).unwrap_err();
+        assert_eq!(
+            err,
+            ListError::IdOverflow {
+                list: "right",
+                index: 1
+            }
+        );
+        assert_eq!(err.to_string(), "right list: ID #2 does not fit in an i64");
+
+        let err = statistics([i64::MAX; 2], [i64::MAX; 2]).unwrap_err();
+        assert_eq!(err.to_string(), "similarity overflows");
+    }
+
+    #[test]
+    fn invalid_lines() {
+        assert_eq!(
+            parse_lists("1   2\n3"),
+            Err(ListError::Columns { line: 2, found: 1 })
+        );
+        assert_eq!(
+            parse_lists("1   2   3"),
+            Err(ListError::Columns { line: 1, found: 3 })
+        );
+        assert!(matches!(
+            parse_lists("1   x"),
+            Err(ListError::ParseInt { line: 1, .. })
+        ));
+    }
+}
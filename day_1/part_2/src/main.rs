@@ -1,30 +1,34 @@
-use std::collections::HashMap;
+use locations::{parse_lists, statistics, ListError, Statistics};
 
 const INPUT: &str = include_str!("../input.txt");
 
 fn main() {
-    println!("Answer: {}", process(INPUT));
+    match std::env::args().nth(1).as_deref() {
+        Some("stats") => {
+            let stats = compute(INPUT).unwrap();
+            println!("Distance: {}", stats.distance);
+            println!("Similarity: {}", stats.similarity);
+            if let Some(gap) = stats.median_gap {
+                println!("Median gap: {}", gap);
+            }
+            println!(
+                "Most repeated ({} times): {:?}",
+                stats.most_repeated.0, stats.most_repeated.1
+            );
+            println!("Left only: {:?}", stats.left_only);
+            println!("Right only: {:?}", stats.right_only);
+        }
+        _ => println!("Answer: {}", process(INPUT)),
+    }
 }
 
-fn process(input: &str) -> i32 {
-    let mut left: Vec<i32> = vec![];
-    let mut right: Vec<i32> = vec![];
-
-    input
-        .lines()
-        .map(|s| s.split_whitespace().map(|n| n.parse::<i32>().unwrap()))
-        .for_each(|mut l| {
-            left.push(l.next().unwrap());
-            right.push(l.next().unwrap());
-        });
-
-    let mut counts: HashMap<i32, i32> = HashMap::new();
-    right
-        .into_iter()
-        .for_each(|n| *counts.entry(n).or_insert(0) += 1);
+fn compute(input: &str) -> Result<Statistics, ListError> {
+    let (left, right) = parse_lists(input)?;
+    statistics(left, right)
+}
 
-    left.into_iter()
-        .fold(0, |acc, x| acc + x * counts.get(&x).unwrap_or(&0))
+fn process(input: &str) -> i64 {
+    compute(input).unwrap().similarity
 }
 
 #[cfg(test)]
@@ -1,64 +1,303 @@
+use std::{cmp::Reverse, collections::VecDeque, ops::RangeInclusive, str::FromStr};
+
 const INPUT: &str = include_str!("../input.txt");
 
 const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const DIAGONALS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+#[derive(Debug, PartialEq)]
+enum TrailError {
+    InvalidHeight,
+    CyclicRules,
 }
 
-fn process(input: &str) -> i32 {
-    let map = input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).unwrap() as isize)
-                .collect()
-        })
-        .collect::<Vec<Vec<isize>>>();
+#[derive(Clone, Debug)]
+struct HikingRules {
+    delta: RangeInclusive<isize>,
+    diagonals: bool,
+    start: isize,
+    end: isize,
+}
 
-    let trailheads = find_trailheads(&map);
+impl Default for HikingRules {
+    fn default() -> Self {
+        HikingRules {
+            delta: 1..=1,
+            diagonals: false,
+            start: 0,
+            end: 9,
+        }
+    }
+}
 
-    trailheads
-        .iter()
-        .map(|t| calculate_trailhead_score(&map, *t))
-        .sum()
+impl HikingRules {
+    fn allows(&self, from: isize, to: isize) -> bool {
+        self.delta.contains(&(to - from))
+    }
+
+    fn directions(&self) -> Vec<(isize, isize)> {
+        let mut directions = DIRECTIONS.to_vec();
+        if self.diagonals {
+            directions.extend(DIAGONALS);
+        }
+        directions
+    }
 }
 
-fn find_trailheads(map: &[Vec<isize>]) -> Vec<(isize, isize)> {
-    let mut trailheads = vec![];
-    for i in 0..map.len() {
-        for j in 0..map[0].len() {
-            if map[i][j] == 0 {
-                trailheads.push((i as isize, j as isize));
-            }
+#[derive(Clone, Debug, Default)]
+struct SummitSet {
+    words: Vec<u64>,
+}
+
+impl SummitSet {
+    fn new(nb_summits: usize) -> Self {
+        SummitSet {
+            words: vec![0; nb_summits.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, summit: usize) {
+        self.words[summit / 64] |= 1 << (summit % 64);
+    }
+
+    fn union_with(&mut self, other: &SummitSet) {
+        for (w, o) in self.words.iter_mut().zip(&other.words) {
+            *w |= o;
         }
     }
-    trailheads
+
+    fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Trailhead {
+    position: (usize, usize),
+    score: usize,
+    // Trails can loop forever when steps may keep the same height
+    rating: Result<usize, TrailError>,
+}
+
+struct TrailMap {
+    heights: Vec<Vec<Option<isize>>>,
+    rules: HikingRules,
+    summits: Vec<Vec<SummitSet>>,
+    ratings: Option<Vec<Vec<usize>>>,
 }
 
-fn calculate_trailhead_score(map: &[Vec<isize>], trailhead: (isize, isize)) -> i32 {
-    let val = map[trailhead.0 as usize][trailhead.1 as usize];
-    if val == 9 {
-        return 1;
+impl FromStr for TrailMap {
+    type Err = TrailError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(TrailMap::new(parse_heights(s)?, HikingRules::default()))
     }
+}
 
-    around_pos(map, &trailhead)
-        .iter()
-        .filter(|pos| map[pos.0 as usize][pos.1 as usize] == val + 1)
-        .map(|pos| calculate_trailhead_score(map, *pos))
-        .sum()
+impl TrailMap {
+    // Single pass from the summits back to the trailheads: a cell reaches the union
+    // of the summits its next steps reach, and has as many trails as they have
+    // combined. Steps must all go the same way in height for that order to exist,
+    // otherwise each trailhead searches its summits on its own and trails are not
+    // counted.
+    fn new(heights: Vec<Vec<Option<isize>>>, rules: HikingRules) -> Self {
+        let mut cells: Vec<(usize, usize)> = vec![];
+        for (i, row) in heights.iter().enumerate() {
+            for (j, h) in row.iter().enumerate() {
+                if h.is_some() {
+                    cells.push((i, j));
+                }
+            }
+        }
+        if *rules.delta.start() > 0 {
+            cells.sort_by_key(|pos| Reverse(heights[pos.0][pos.1]));
+        } else {
+            cells.sort_by_key(|pos| heights[pos.0][pos.1]);
+        }
+
+        let summit_cells: Vec<(usize, usize)> = cells
+            .iter()
+            .filter(|pos| heights[pos.0][pos.1] == Some(rules.end))
+            .copied()
+            .collect();
+        let nb_summits = summit_cells.len();
+
+        let mut map = TrailMap {
+            summits: heights
+                .iter()
+                .map(|row| vec![SummitSet::default(); row.len()])
+                .collect(),
+            ratings: None,
+            heights,
+            rules,
+        };
+
+        if map.rules.delta.contains(&0) {
+            map.search_summits(&summit_cells);
+            return map;
+        }
+
+        let mut ratings: Vec<Vec<usize>> =
+            map.heights.iter().map(|row| vec![0; row.len()]).collect();
+        for (id, pos) in summit_cells.iter().enumerate() {
+            let mut summits = SummitSet::new(nb_summits);
+            summits.insert(id);
+            map.summits[pos.0][pos.1] = summits;
+            ratings[pos.0][pos.1] = 1;
+        }
+
+        for pos in cells {
+            let height = map.get(pos).unwrap();
+            if height == map.rules.end {
+                continue;
+            }
+
+            let mut summits = SummitSet::new(nb_summits);
+            let mut rating = 0;
+            for next in map.around_pos(pos) {
+                if map
+                    .get(next)
+                    .is_some_and(|next_height| map.rules.allows(height, next_height))
+                {
+                    summits.union_with(&map.summits[next.0][next.1]);
+                    rating += ratings[next.0][next.1];
+                }
+            }
+            map.summits[pos.0][pos.1] = summits;
+            ratings[pos.0][pos.1] = rating;
+        }
+        map.ratings = Some(ratings);
+
+        map
+    }
+
+    // Breadth-first search from every trailhead, for rules where trails can loop.
+    fn search_summits(&mut self, summit_cells: &[(usize, usize)]) {
+        for (i, row) in self.heights.iter().enumerate() {
+            for (j, h) in row.iter().enumerate() {
+                if *h != Some(self.rules.start) {
+                    continue;
+                }
+
+                let mut summits = SummitSet::new(summit_cells.len());
+                let mut visited: Vec<Vec<bool>> = self
+                    .heights
+                    .iter()
+                    .map(|row| vec![false; row.len()])
+                    .collect();
+                let mut queue = VecDeque::from([(i, j)]);
+                visited[i][j] = true;
+
+                while let Some(pos) = queue.pop_front() {
+                    let height = self.get(pos).unwrap();
+                    if height == self.rules.end {
+                        summits.insert(summit_cells.iter().position(|s| *s == pos).unwrap());
+                        continue;
+                    }
+
+                    for next in self.around_pos(pos) {
+                        if !visited[next.0][next.1]
+                            && self
+                                .get(next)
+                                .is_some_and(|next_height| self.rules.allows(height, next_height))
+                        {
+                            visited[next.0][next.1] = true;
+                            queue.push_back(next);
+                        }
+                    }
+                }
+
+                self.summits[i][j] = summits;
+            }
+        }
+    }
+
+    fn get(&self, pos: (usize, usize)) -> Option<isize> {
+        self.heights[pos.0][pos.1]
+    }
+
+    fn trailheads(&self) -> Vec<Trailhead> {
+        let mut trailheads = vec![];
+        for (i, row) in self.heights.iter().enumerate() {
+            for (j, h) in row.iter().enumerate() {
+                if *h == Some(self.rules.start) {
+                    trailheads.push(Trailhead {
+                        position: (i, j),
+                        score: self.summits[i][j].len(),
+                        rating: self
+                            .ratings
+                            .as_ref()
+                            .map(|r| r[i][j])
+                            .ok_or(TrailError::CyclicRules),
+                    });
+                }
+            }
+        }
+        trailheads
+    }
+
+    fn best_trailheads(&self) -> Vec<Trailhead> {
+        let mut trailheads = self.trailheads();
+        trailheads.sort_by_key(|t| Reverse((t.score, t.rating.as_ref().ok().copied())));
+        trailheads
+    }
+
+    fn around_pos(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        self.rules
+            .directions()
+            .iter()
+            .filter_map(|d| {
+                let new_pos = (
+                    pos.0.checked_add_signed(d.0)?,
+                    pos.1.checked_add_signed(d.1)?,
+                );
+                if new_pos.0 < self.heights.len() && new_pos.1 < self.heights[new_pos.0].len() {
+                    Some(new_pos)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
-fn around_pos(map: &[Vec<isize>], pos: &(isize, isize)) -> Vec<(isize, isize)> {
-    DIRECTIONS
-        .iter()
-        .map(|d| (d.0 + pos.0, d.1 + pos.1))
-        .filter(|pos| valid_pos((map.len(), map[0].len()), pos))
+fn parse_heights(s: &str) -> Result<Vec<Vec<Option<isize>>>, TrailError> {
+    s.lines()
+        .map(|l| {
+            l.chars()
+                .map(|c| match c {
+                    '.' => Ok(None),
+                    _ => c
+                        .to_digit(10)
+                        .map(|d| Some(d as isize))
+                        .ok_or(TrailError::InvalidHeight),
+                })
+                .collect()
+        })
         .collect()
 }
 
-fn valid_pos(map_size: (usize, usize), pos: &(isize, isize)) -> bool {
-    pos.0 >= 0 && pos.1 >= 0 && pos.0 < map_size.0 as isize && pos.1 < map_size.1 as isize
+fn main() {
+    println!("Answer: {}", process(INPUT));
+
+    let map = TrailMap::from_str(INPUT).unwrap();
+    if let Some(best) = map.best_trailheads().first() {
+        println!(
+            "Best trailhead: {:?} (score {}, rating {})",
+            best.position,
+            best.score,
+            best.rating.as_ref().unwrap()
+        );
+    }
+}
+
+fn process(input: &str) -> usize {
+    TrailMap::from_str(input)
+        .unwrap()
+        .trailheads()
+        .iter()
+        .map(|t| t.rating.as_ref().unwrap())
+        .sum()
 }
 
 #[cfg(test)]
@@ -77,4 +316,102 @@ mod tests {
 10456732"#;
         assert_eq!(process(input), 81)
     }
+
+    #[test]
+    fn best_trailheads() {
+        let input = r#"0123
+7654
+89.0
+9..."#;
+        let map = TrailMap::from_str(input).unwrap();
+        assert_eq!(
+            map.best_trailheads().first(),
+            Some(&Trailhead {
+                position: (0, 0),
+                score: 2,
+                rating: Ok(2)
+            })
+        );
+        assert_eq!(map.trailheads().iter().map(|t| t.score).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn impassable_cells() {
+        let input = r#".....0.
+..4321.
+..5..2.
+..6543.
+..7..4.
+..8765.
+..9...."#;
+        assert_eq!(process(input), 3);
+
+        let input = r#"..90..9
+...1.98
+...2..7
+6543456
+765.987
+876....
+987...."#;
+        assert_eq!(process(input), 13);
+
+        let input = r#"012345
+123456
+234567
+345678
+4.6789
+56789."#;
+        assert_eq!(process(input), 227);
+    }
+
+    #[test]
+    fn custom_rules() {
+        let input = r#"0123
+1234
+8765
+9976"#;
+        let heights = parse_heights(input).unwrap();
+
+        let descending = HikingRules {
+            delta: -1..=-1,
+            start: 9,
+            end: 0,
+            ..Default::default()
+        };
+        let map = TrailMap::new(heights.clone(), descending);
+        assert_eq!(
+            map.trailheads()
+                .iter()
+                .map(|t| t.rating.as_ref().unwrap())
+                .sum::<usize>(),
+            4
+        );
+
+        let diagonals = HikingRules {
+            diagonals: true,
+            ..Default::default()
+        };
+        let map = TrailMap::new(heights.clone(), diagonals);
+        assert_eq!(map.trailheads().iter().map(|t| t.score).sum::<usize>(), 2);
+
+        let any_step = HikingRules {
+            delta: -1..=1,
+            ..Default::default()
+        };
+        let map = TrailMap::new(heights.clone(), any_step);
+        assert_eq!(map.trailheads().iter().map(|t| t.score).sum::<usize>(), 1);
+        assert!(map
+            .trailheads()
+            .iter()
+            .all(|t| t.rating == Err(TrailError::CyclicRules)));
+
+        // The summit is only reachable through the flat step
+        assert_eq!(process("0119"), 0);
+        let flat = HikingRules {
+            delta: 0..=8,
+            ..Default::default()
+        };
+        let map = TrailMap::new(parse_heights("0119").unwrap(), flat);
+        assert_eq!(map.trailheads()[0].score, 1);
+    }
 }
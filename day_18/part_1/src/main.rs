@@ -1,4 +1,3 @@
-use core::panic;
 use std::{cmp::Ordering, collections::BinaryHeap};
 
 const INPUT: &str = include_str!("../input.txt");
@@ -10,11 +9,12 @@ struct Space {
     h_cost: usize,
     cost: usize,
     pos: (usize, usize),
+    from: Option<(usize, usize)>,
 }
 
 impl PartialOrd for Space {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(other.h_cost.cmp(&self.h_cost))
+        Some(self.cmp(other))
     }
 }
 
@@ -37,19 +37,36 @@ fn manhattan_distance(a: (usize, usize), b: (usize, usize)) -> usize {
     a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
 }
 
-fn find_path(end: (usize, usize), visited: &mut [Vec<bool>], size: usize) -> usize {
+/// Returns the cells of a shortest path from `(0, 0)` to `end`, both included.
+fn find_path(
+    end: (usize, usize),
+    visited: &mut [Vec<bool>],
+    size: usize,
+) -> Option<Vec<(usize, usize)>> {
+    let mut from: Vec<Vec<Option<(usize, usize)>>> = vec![vec![None; size]; size];
     let mut queue: BinaryHeap<Space> = BinaryHeap::new();
     queue.push(Space {
         h_cost: manhattan_distance((0, 0), end),
         cost: 0,
         pos: (0, 0),
+        from: None,
     });
 
     while let Some(curr) = queue.pop() {
-        if curr.pos == end {
-            return curr.cost;
+        if visited[curr.pos.1][curr.pos.0] {
+            continue;
         }
         visited[curr.pos.1][curr.pos.0] = true;
+        from[curr.pos.1][curr.pos.0] = curr.from;
+
+        if curr.pos == end {
+            let mut path = vec![end];
+            while let Some(prev) = from[path.last().unwrap().1][path.last().unwrap().0] {
+                path.push(prev);
+            }
+            path.reverse();
+            return Some(path);
+        }
 
         DIRECTIONS.iter().for_each(|delta| {
             if let Some(new_pos) = increment_pos(curr.pos, *delta, size) {
@@ -58,24 +75,116 @@ fn find_path(end: (usize, usize), visited: &mut [Vec<bool>], size: usize) -> usi
                         h_cost: curr.cost + 1 + manhattan_distance(new_pos, end),
                         cost: curr.cost + 1,
                         pos: new_pos,
+                        from: Some(curr.pos),
                     });
                 }
             }
         })
     }
 
-    panic!("no path found")
+    None
 }
 
-fn main() {
-    println!("Answer: {}", process(INPUT, 71, 1024));
+struct Step {
+    nb_bytes: usize,
+    path: Option<Vec<(usize, usize)>>,
 }
 
-fn process(input: &str, size: usize, nb_bytes: usize) -> usize {
-    let mut visited: Vec<Vec<bool>> = vec![vec![false; size]; size];
+struct Timeline {
+    steps: Vec<Step>,
+    // Indices of the bytes whose fall made the path longer the first time, and
+    // made it disappear. When the exit is already cut off by the first bytes,
+    // the path is not followed before that so it is never found to be longer.
+    lengthened: Option<usize>,
+    cut_off: Option<usize>,
+}
+
+impl Timeline {
+    // Falling bytes can only make the path longer, so it is only searched again
+    // when a byte lands on it.
+    fn new(bytes: &[(usize, usize)], size: usize, nb_bytes: usize) -> Self {
+        let nb_bytes = nb_bytes.min(bytes.len());
+        let end = (size - 1, size - 1);
+        let mut corrupted: Vec<Vec<bool>> = vec![vec![false; size]; size];
+        for pos in &bytes[..nb_bytes] {
+            corrupted[pos.1][pos.0] = true;
+        }
+
+        let mut timeline = Timeline {
+            steps: vec![Step {
+                nb_bytes,
+                path: find_path(end, &mut corrupted.clone(), size),
+            }],
+            lengthened: None,
+            cut_off: None,
+        };
+        if timeline.steps[0].path.is_none() {
+            timeline.cut_off = first_cut_off(&bytes[..nb_bytes], size);
+            return timeline;
+        }
+
+        for (i, pos) in bytes.iter().enumerate().skip(nb_bytes) {
+            corrupted[pos.1][pos.0] = true;
+
+            let prev = timeline.steps.last().unwrap().path.as_ref().unwrap();
+            let path = if prev.contains(pos) {
+                find_path(end, &mut corrupted.clone(), size)
+            } else {
+                Some(prev.clone())
+            };
+
+            match &path {
+                None => timeline.cut_off = Some(i),
+                Some(path) if timeline.lengthened.is_none() && path.len() > prev.len() => {
+                    timeline.lengthened = Some(i)
+                }
+                _ => (),
+            }
+            timeline.steps.push(Step {
+                nb_bytes: i + 1,
+                path,
+            });
+
+            if timeline.cut_off.is_some() {
+                break;
+            }
+        }
+
+        timeline
+    }
+}
+
+// Bisects the number of fallen bytes for the first one cutting off the exit,
+// `None` when the exit can be reached even after all of them.
+fn first_cut_off(bytes: &[(usize, usize)], size: usize) -> Option<usize> {
+    let end = (size - 1, size - 1);
+    let cut_off = |nb_bytes: usize| {
+        let mut corrupted: Vec<Vec<bool>> = vec![vec![false; size]; size];
+        for pos in &bytes[..nb_bytes] {
+            corrupted[pos.1][pos.0] = true;
+        }
+        find_path(end, &mut corrupted, size).is_none()
+    };
+
+    // The first `low` bytes leave a path, the first `high` ones do not
+    let (mut low, mut high) = (0, bytes.len());
+    if !cut_off(high) {
+        return None;
+    }
+    while high - low > 1 {
+        let middle = (low + high) / 2;
+        if cut_off(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    high.checked_sub(1)
+}
+
+fn parse_bytes(input: &str) -> Vec<(usize, usize)> {
     input
         .lines()
-        .take(nb_bytes)
         .map(|l| {
             let mut parts = l.split(',');
             (
@@ -83,10 +192,52 @@ fn process(input: &str, size: usize, nb_bytes: usize) -> usize {
                 parts.next().unwrap().parse::<usize>().unwrap(),
             )
         })
+        .collect()
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, params @ ..] if command == "timeline" => {
+            let size = params.first().map_or(71, |s| s.parse().unwrap());
+            let nb_bytes = params.get(1).map_or(1024, |s| s.parse().unwrap());
+
+            let bytes = parse_bytes(INPUT);
+            let timeline = Timeline::new(&bytes, size, nb_bytes);
+            for step in &timeline.steps {
+                match &step.path {
+                    Some(path) => println!(
+                        "{} bytes: {} steps {:?}",
+                        step.nb_bytes,
+                        path.len() - 1,
+                        path
+                    ),
+                    None => println!("{} bytes: no path", step.nb_bytes),
+                }
+            }
+            if let Some(i) = timeline.lengthened {
+                println!("First byte lengthening the path: #{} {:?}", i + 1, bytes[i]);
+            }
+            if let Some(i) = timeline.cut_off {
+                println!("Byte cutting off the exit: #{} {:?}", i + 1, bytes[i]);
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT, 71, 1024)),
+    }
+}
+
+fn process(input: &str, size: usize, nb_bytes: usize) -> usize {
+    let mut visited: Vec<Vec<bool>> = vec![vec![false; size]; size];
+    parse_bytes(input)
+        .iter()
+        .take(nb_bytes)
         .for_each(|pos| visited[pos.1][pos.0] = true);
     let end = (size - 1, size - 1);
 
     find_path(end, &mut visited, size)
+        .expect("no path found")
+        .len()
+        - 1
 }
 
 #[cfg(test)]
@@ -122,4 +273,73 @@ mod tests {
 2,0"#;
         assert_eq!(process(input, 7, 12), 22)
     }
+
+    #[test]
+    fn timeline() {
+        // Two walls falling one byte at a time force a longer and longer
+        // detour, until the gap at the top closes
+        let input = r#"1,0
+1,1
+1,2
+1,3
+3,4
+3,3
+3,2
+3,1
+3,0
+2,2
+0,3"#;
+        let bytes = parse_bytes(input);
+        let timeline = Timeline::new(&bytes, 5, 4);
+
+        let first = timeline.steps[0].path.as_ref().unwrap();
+        assert_eq!((first[0], first[8]), ((0, 0), (4, 4)));
+        assert!(first
+            .windows(2)
+            .all(|w| manhattan_distance(w[0], w[1]) == 1 && !bytes[..4].contains(&w[1])));
+
+        assert_eq!(timeline.cut_off, Some(8));
+        assert_eq!(timeline.steps.len(), 6);
+        assert!(timeline.steps.last().unwrap().path.is_none());
+
+        let lengths: Vec<usize> = timeline.steps[..5]
+            .iter()
+            .map(|s| s.path.as_ref().unwrap().len() - 1)
+            .collect();
+        assert_eq!(lengths, vec![8, 10, 12, 14, 16]);
+        assert_eq!(timeline.lengthened, Some(4));
+    }
+
+    #[test]
+    fn timeline_bounds() {
+        let input = r#"1,0
+1,1
+1,2
+1,3
+3,4
+3,3
+3,2
+3,1
+3,0
+2,2
+0,3"#;
+        let bytes = parse_bytes(input);
+
+        let timeline = Timeline::new(&bytes, 5, 9);
+        assert_eq!(timeline.steps.len(), 1);
+        assert!(timeline.steps[0].path.is_none());
+        assert_eq!(timeline.cut_off, Some(8));
+
+        // Byte #9 at 3,0 cuts off the exit however many bytes fell after it
+        for nb_bytes in [10, 11] {
+            let timeline = Timeline::new(&bytes, 5, nb_bytes);
+            assert_eq!(timeline.steps.len(), 1);
+            assert_eq!(timeline.cut_off, Some(8));
+        }
+
+        let timeline = Timeline::new(&bytes[..4], 5, 1024);
+        assert_eq!(timeline.steps.len(), 1);
+        assert_eq!(timeline.steps[0].nb_bytes, 4);
+        assert_eq!(timeline.cut_off, None);
+    }
 }
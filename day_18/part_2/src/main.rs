@@ -1,5 +1,5 @@
 use core::panic;
-use std::{cmp::Ordering, collections::BinaryHeap, usize};
+use std::{cmp::Ordering, collections::BinaryHeap};
 
 const INPUT: &str = include_str!("../input.txt");
 
@@ -14,7 +14,7 @@ struct Space {
 
 impl PartialOrd for Space {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(other.h_cost.cmp(&self.h_cost))
+        Some(self.cmp(other))
     }
 }
 
@@ -68,15 +68,135 @@ fn path_exits(end: (usize, usize), corrupted: &[Vec<bool>], size: usize) -> bool
     false
 }
 
-fn main() {
-    println!("Answer: {}", process(INPUT, 71));
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
 }
 
-fn process(input: &str, size: usize) -> String {
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut x = x;
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        match self.rank[a].cmp(&self.rank[b]) {
+            Ordering::Less => self.parent[a] = b,
+            Ordering::Greater => self.parent[b] = a,
+            Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+    }
+
+    fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+// Starts from the grid with every byte fallen and lifts them back in reverse
+// order: the first byte whose removal joins the start and the exit is the one
+// that cut them apart.
+fn first_blocking_byte(bytes: &[(usize, usize)], size: usize) -> Option<usize> {
+    let index = |pos: (usize, usize)| pos.1 * size + pos.0;
+    let start = index((0, 0));
+    let end = index((size - 1, size - 1));
+
+    // A cell only clears once every byte that fell on it is lifted
+    let mut corrupted = vec![0; size * size];
+    for pos in bytes {
+        corrupted[index(*pos)] += 1;
+    }
+
+    let mut cells = UnionFind::new(size * size);
+    let free = |cells: &mut UnionFind, corrupted: &[usize], pos: (usize, usize)| {
+        for delta in DIRECTIONS {
+            if let Some(new_pos) = increment_pos(pos, delta, size) {
+                if corrupted[index(new_pos)] == 0 {
+                    cells.union(index(pos), index(new_pos));
+                }
+            }
+        }
+    };
+
+    for y in 0..size {
+        for x in 0..size {
+            if corrupted[index((x, y))] == 0 {
+                free(&mut cells, &corrupted, (x, y));
+            }
+        }
+    }
+    if cells.connected(start, end) {
+        return None;
+    }
+
+    for (i, pos) in bytes.iter().enumerate().rev() {
+        corrupted[index(*pos)] -= 1;
+        if corrupted[index(*pos)] == 0 {
+            free(&mut cells, &corrupted, *pos);
+            if corrupted[start] == 0 && corrupted[end] == 0 && cells.connected(start, end) {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
+
+fn bisect_blocking_byte(bytes: &[(usize, usize)], size: usize) -> Option<usize> {
     let end = (size - 1, size - 1);
+    let blocked = |nb_bytes: usize| {
+        let mut corrupted: Vec<Vec<bool>> = vec![vec![false; size]; size];
+        for pos in &bytes[..nb_bytes] {
+            corrupted[pos.1][pos.0] = true;
+        }
+        corrupted[0][0] || corrupted[end.1][end.0] || !path_exits(end, &corrupted, size)
+    };
+
+    if !blocked(bytes.len()) {
+        return None;
+    }
 
-    let mut corrupted: Vec<Vec<bool>> = vec![vec![false; size]; size];
-    let bytes = input
+    // Smallest number of fallen bytes blocking the path, the last of them is the culprit
+    let (mut low, mut high) = (0, bytes.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        if blocked(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Some(low - 1)
+}
+
+fn parse_bytes(input: &str) -> Vec<(usize, usize)> {
+    input
         .lines()
         .map(|l| {
             let mut parts = l.split(',');
@@ -85,13 +205,27 @@ fn process(input: &str, size: usize) -> String {
                 parts.next().unwrap().parse::<usize>().unwrap(),
             )
         })
-        .collect::<Vec<(usize, usize)>>();
+        .collect()
+}
 
-    if let Some(byte) = bytes.iter().find(|pos| {
-        corrupted[pos.1][pos.0] = true;
-        !path_exits(end, &corrupted, size)
-    }) {
-        format!("{},{}", byte.0, byte.1).to_string()
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("bisect") => {
+            let bytes = parse_bytes(INPUT);
+            match bisect_blocking_byte(&bytes, 71) {
+                Some(i) => println!("Answer: {},{}", bytes[i].0, bytes[i].1),
+                None => println!("No bytes block path"),
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT, 71)),
+    }
+}
+
+fn process(input: &str, size: usize) -> String {
+    let bytes = parse_bytes(input);
+
+    if let Some(i) = first_blocking_byte(&bytes, size) {
+        format!("{},{}", bytes[i].0, bytes[i].1)
     } else {
         panic!("no bytes block path")
     }
@@ -130,4 +264,32 @@ mod tests {
 2,0"#;
         assert_eq!(process(input, 7), "6,1")
     }
+
+    #[test]
+    fn bisect() {
+        let input = r#"1,0
+1,1
+1,2
+1,3
+3,4
+3,3
+3,2
+3,1
+3,0
+2,2
+0,3"#;
+        let bytes = parse_bytes(input);
+        assert_eq!(bisect_blocking_byte(&bytes, 5), Some(8));
+        assert_eq!(first_blocking_byte(&bytes, 5), Some(8));
+
+        assert_eq!(first_blocking_byte(&bytes[..8], 5), None);
+        assert_eq!(bisect_blocking_byte(&bytes[..8], 5), None);
+    }
+
+    #[test]
+    fn blocked_corners() {
+        let bytes = [(1, 1), (0, 0), (0, 0), (2, 2)];
+        assert_eq!(first_blocking_byte(&bytes, 3), Some(1));
+        assert_eq!(bisect_blocking_byte(&bytes, 3), Some(1));
+    }
 }
@@ -1,48 +1,230 @@
-use std::collections::HashMap;
-
 const INPUT: &str = include_str!("../input.txt");
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+#[derive(Default)]
+struct Node {
+    children: Vec<(u8, usize)>,
+    towel: bool,
 }
 
-fn process(input: &str) -> usize {
-    let mut sections = input.split("\n\n");
+/// Trie over the towels, to find every towel starting at a given offset of a
+/// design in a single walk.
+struct TowelIndex {
+    nodes: Vec<Node>,
+}
 
-    let towels = sections.next().unwrap().split(", ").collect::<Vec<&str>>();
-    let patterns = sections.next().unwrap().lines().collect::<Vec<&str>>();
+impl TowelIndex {
+    fn new(towels: &[&str]) -> Self {
+        let mut index = TowelIndex {
+            nodes: vec![Node::default()],
+        };
+
+        for towel in towels {
+            let mut node = 0;
+            for &color in towel.as_bytes() {
+                node = match index.child(node, color) {
+                    Some(child) => child,
+                    None => {
+                        index.nodes.push(Node::default());
+                        let child = index.nodes.len() - 1;
+                        index.nodes[node].children.push((color, child));
+                        child
+                    }
+                };
+            }
+            index.nodes[node].towel = true;
+        }
+
+        index
+    }
+
+    fn child(&self, node: usize, color: u8) -> Option<usize> {
+        self.nodes[node]
+            .children
+            .iter()
+            .find(|(c, _)| *c == color)
+            .map(|(_, child)| *child)
+    }
+
+    /// Offsets at which a towel placed at `start` of the design ends, shortest
+    /// towel first.
+    fn ends(&self, design: &str, start: usize) -> Vec<usize> {
+        let mut ends = vec![];
+        let mut node = 0;
+        for (i, &color) in design.as_bytes()[start..].iter().enumerate() {
+            match self.child(node, color) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if self.nodes[node].towel {
+                ends.push(start + i + 1);
+            }
+        }
+        ends
+    }
+
+    fn arrange<'a>(&self, design: &'a str) -> Arrangement<'a> {
+        let ends: Vec<Vec<usize>> = (0..design.len()).map(|i| self.ends(design, i)).collect();
+
+        let mut ways = vec![0; design.len() + 1];
+        ways[design.len()] = 1;
+        for i in (0..design.len()).rev() {
+            ways[i] = ends[i].iter().map(|&e| ways[e]).sum();
+        }
+
+        Arrangement { design, ends, ways }
+    }
+}
+
+struct Arrangement<'a> {
+    design: &'a str,
+    ends: Vec<Vec<usize>>,
+    // Number of ways to build the design from each offset to its end
+    ways: Vec<u128>,
+}
+
+impl<'a> Arrangement<'a> {
+    fn count(&self) -> u128 {
+        self.ways[0]
+    }
+
+    // A shorter towel comes before any longer one sharing its colors, so the
+    // first arrangement always takes the shortest towel that still leads to the end.
+    fn first(&self) -> Option<Vec<&'a str>> {
+        self.iter().next()
+    }
+
+    fn iter(&self) -> Arrangements<'_, 'a> {
+        Arrangements {
+            arrangement: self,
+            stack: vec![],
+            started: false,
+        }
+    }
+
+    fn longest_prefix(&self) -> &'a str {
+        let mut reachable = vec![false; self.design.len() + 1];
+        reachable[0] = true;
+        for i in 0..self.design.len() {
+            if reachable[i] {
+                for &e in &self.ends[i] {
+                    reachable[e] = true;
+                }
+            }
+        }
+
+        let len = reachable.iter().rposition(|r| *r).unwrap();
+        &self.design[..len]
+    }
+
+    fn viable_ends(&self, start: usize) -> impl Iterator<Item = usize> + '_ {
+        self.ends[start]
+            .iter()
+            .copied()
+            .filter(|&e| self.ways[e] > 0)
+    }
+}
 
-    patterns
-        .iter()
-        .map(|p| count_possible_patterns(&towels, p, 0, &mut HashMap::new()))
-        .sum()
+/// Every arrangement of a design in lexicographic order, built lazily by only
+/// following towels that lead to the end of the design.
+struct Arrangements<'r, 'a> {
+    arrangement: &'r Arrangement<'a>,
+    // Offset of each towel placed so far, and which of its viable ends was taken
+    stack: Vec<(usize, usize)>,
+    started: bool,
 }
 
-fn count_possible_patterns(
-    towels: &[&str],
-    pattern: &str,
-    done: usize,
-    memo: &mut HashMap<usize, usize>,
-) -> usize {
-    if done == pattern.len() {
-        1
-    } else {
-        let p = &pattern[done..];
-        if let Some(cost) = memo.get(&done) {
-            return *cost;
+impl<'a> Arrangements<'_, 'a> {
+    fn end_of(&self, (start, choice): (usize, usize)) -> usize {
+        self.arrangement.viable_ends(start).nth(choice).unwrap()
+    }
+
+    fn descend(&mut self, mut pos: usize) {
+        while pos < self.arrangement.design.len() {
+            self.stack.push((pos, 0));
+            pos = self.end_of((pos, 0));
         }
+    }
 
-        let res: usize = towels
+    fn towels(&self) -> Vec<&'a str> {
+        self.stack
             .iter()
-            .filter(|t| p.starts_with(**t))
-            .map(|t| count_possible_patterns(towels, pattern, done + t.len(), memo))
-            .sum();
+            .map(|&(start, choice)| &self.arrangement.design[start..self.end_of((start, choice))])
+            .collect()
+    }
+}
+
+impl<'a> Iterator for Arrangements<'_, 'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.arrangement.count() == 0 {
+                return None;
+            }
+            self.descend(0);
+            return Some(self.towels());
+        }
+
+        while let Some((start, choice)) = self.stack.pop() {
+            if choice + 1 < self.arrangement.viable_ends(start).count() {
+                self.stack.push((start, choice + 1));
+                let end = self.end_of((start, choice + 1));
+                self.descend(end);
+                return Some(self.towels());
+            }
+        }
+
+        None
+    }
+}
+
+fn parse(input: &str) -> (TowelIndex, Vec<&str>) {
+    let mut sections = input.split("\n\n");
+
+    let towels = sections.next().unwrap().split(", ").collect::<Vec<&str>>();
+    let designs = sections.next().unwrap().lines().collect::<Vec<&str>>();
+
+    (TowelIndex::new(&towels), designs)
+}
 
-        memo.insert(done, res);
-        res
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command] if command == "explain" => {
+            let (index, designs) = parse(INPUT);
+            for design in designs {
+                let arrangement = index.arrange(design);
+                match arrangement.first() {
+                    Some(first) => println!(
+                        "{}: {} arrangements, first {}",
+                        design,
+                        arrangement.count(),
+                        first.join(",")
+                    ),
+                    None => println!(
+                        "{}: impossible, longest prefix {:?}",
+                        design,
+                        arrangement.longest_prefix()
+                    ),
+                }
+            }
+        }
+        [command, design] if command == "list" => {
+            let (index, _) = parse(INPUT);
+            for towels in index.arrange(design).iter() {
+                println!("{}", towels.join(","));
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT)),
     }
 }
 
+fn process(input: &str) -> u128 {
+    let (index, designs) = parse(input);
+    designs.iter().map(|d| index.arrange(d).count()).sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,4 +243,52 @@ brgr
 bbrgwb"#;
         assert_eq!(process(input), 16)
     }
+
+    #[test]
+    fn arrangements() {
+        let index = TowelIndex::new(&["r", "b", "g", "rb", "gb", "br"]);
+
+        let arrangement = index.arrange("gbbr");
+        assert_eq!(arrangement.count(), 4);
+        assert_eq!(
+            arrangement.iter().collect::<Vec<_>>(),
+            vec![
+                vec!["g", "b", "b", "r"],
+                vec!["g", "b", "br"],
+                vec!["gb", "b", "r"],
+                vec!["gb", "br"],
+            ]
+        );
+        assert_eq!(arrangement.first(), Some(vec!["g", "b", "b", "r"]));
+
+        let arrangement = index.arrange("rrbgbr");
+        assert_eq!(arrangement.iter().count(), 6);
+        assert_eq!(arrangement.longest_prefix(), "rrbgbr");
+    }
+
+    #[test]
+    fn impossible_designs() {
+        let index = TowelIndex::new(&["r", "b", "g", "rb", "gb", "br"]);
+
+        let arrangement = index.arrange("ubwu");
+        assert_eq!(arrangement.count(), 0);
+        assert_eq!(arrangement.first(), None);
+        assert_eq!(arrangement.longest_prefix(), "");
+
+        let arrangement = index.arrange("bbrgwb");
+        assert_eq!(arrangement.iter().next(), None);
+        assert_eq!(arrangement.longest_prefix(), "bbrg");
+    }
+
+    #[test]
+    fn long_designs() {
+        let index = TowelIndex::new(&["a", "aa"]);
+        let design = "a".repeat(150);
+
+        // Fibonacci numbers, F(151) does not fit in 64 bits
+        assert_eq!(
+            index.arrange(&design).count(),
+            16130531424904581415797907386349
+        );
+    }
 }
@@ -1,48 +1,148 @@
-// Brute force solution
+use std::ops::RangeInclusive;
 
 const INPUT: &str = include_str!("../input.txt");
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+#[derive(Clone, Copy)]
+enum Trend {
+    Increasing,
+    Decreasing,
+    Any,
 }
 
-fn process(input: &str) -> i32 {
-    input
-        .lines()
-        .map(|s| {
-            s.split_whitespace()
-                .map(|n| n.parse().unwrap())
-                .collect::<Vec<i32>>()
-        })
-        .filter(|v| {
-            (0..v.len()).any(|i| {
-                let candidate = v
-                    .iter()
-                    .enumerate()
-                    .filter(|&(j, _)| j != i)
-                    .map(|(_, &x)| x)
-                    .collect::<Vec<i32>>();
-                validate(candidate.iter()) || validate(candidate.iter().rev())
-            })
-        })
-        .count() as i32
+struct Tolerance {
+    step: RangeInclusive<i32>,
+    // Whether all levels must go the same way, otherwise only the size of each
+    // step is checked.
+    strict: bool,
+    removals: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            step: 1..=3,
+            strict: true,
+            removals: 1,
+        }
+    }
 }
 
-fn validate<'a, I>(report: I) -> bool
-where
-    I: Iterator<Item = &'a i32>,
-{
-    let mut prev: Option<&i32> = None;
-    for l in report {
-        if let Some(p) = prev {
-            if l <= p || (l - p) > 3 {
-                return false;
+impl Tolerance {
+    fn allows(&self, prev: i32, next: i32, trend: Trend) -> bool {
+        let diff = next - prev;
+        match trend {
+            Trend::Increasing => self.step.contains(&diff),
+            Trend::Decreasing => self.step.contains(&-diff),
+            Trend::Any => self.step.contains(&diff.abs()),
+        }
+    }
+
+    fn trends(&self) -> Vec<Trend> {
+        if self.strict {
+            vec![Trend::Increasing, Trend::Decreasing]
+        } else {
+            vec![Trend::Any]
+        }
+    }
+
+    /// Returns the indices of the levels to remove for the report to be safe,
+    /// as few as possible, or `None` when it cannot be made safe.
+    fn dampen(&self, report: &[i32]) -> Option<Vec<usize>> {
+        for limit in 0..=self.removals {
+            for trend in self.trends() {
+                let mut removed = vec![];
+                if self.try_removals(report, &mut removed, trend, limit) {
+                    removed.sort();
+                    return Some(removed);
+                }
+            }
+        }
+
+        None
+    }
+
+    // One of the two levels of the first violating step has to go, so each
+    // removal only branches twice.
+    fn try_removals(
+        &self,
+        report: &[i32],
+        removed: &mut Vec<usize>,
+        trend: Trend,
+        limit: usize,
+    ) -> bool {
+        let Some((a, b)) = self.first_violation(report, removed, trend) else {
+            return true;
+        };
+        if removed.len() == limit {
+            return false;
+        }
+
+        for i in [a, b] {
+            removed.push(i);
+            if self.try_removals(report, removed, trend, limit) {
+                return true;
             }
+            removed.pop();
         }
-        prev = Some(l);
+
+        false
     }
 
-    true
+    fn first_violation(
+        &self,
+        report: &[i32],
+        removed: &[usize],
+        trend: Trend,
+    ) -> Option<(usize, usize)> {
+        let mut prev: Option<usize> = None;
+        for i in (0..report.len()).filter(|i| !removed.contains(i)) {
+            if let Some(p) = prev {
+                if !self.allows(report[p], report[i], trend) {
+                    return Some((p, i));
+                }
+            }
+            prev = Some(i);
+        }
+
+        None
+    }
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("report") => {
+            let tolerance = Tolerance::default();
+            for (line, report) in INPUT.lines().zip(parse_reports(INPUT)) {
+                match tolerance.dampen(&report) {
+                    Some(removed) if removed.is_empty() => println!("{}: safe", line),
+                    Some(removed) => {
+                        let levels: Vec<String> = removed
+                            .iter()
+                            .map(|&i| format!("level {} ({})", i, report[i]))
+                            .collect();
+                        println!("{}: safe by removing {}", line, levels.join(", "));
+                    }
+                    None => println!("{}: unsafe", line),
+                }
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT)),
+    }
+}
+
+fn process(input: &str) -> i32 {
+    let tolerance = Tolerance::default();
+    parse_reports(input)
+        .iter()
+        .filter(|r| tolerance.dampen(r).is_some())
+        .count() as i32
+}
+
+fn parse_reports(input: &str) -> Vec<Vec<i32>> {
+    input
+        .lines()
+        .map(|s| s.split_whitespace().map(|n| n.parse().unwrap()).collect())
+        .collect()
 }
 
 #[cfg(test)]
@@ -59,4 +159,53 @@ mod tests {
 1 3 6 7 9"#;
         assert_eq!(process(input), 4)
     }
+
+    #[test]
+    fn removals() {
+        let tolerance = Tolerance::default();
+        for (report, removals) in [
+            (vec![7, 6, 4, 2, 1], Some(vec![])),
+            (vec![1, 2, 7, 8, 9], None),
+            (vec![1, 3, 2, 4, 5], Some(vec![1])),
+            (vec![8, 6, 4, 4, 1], Some(vec![2])),
+        ] {
+            assert_eq!(tolerance.dampen(&report), removals);
+        }
+    }
+
+    #[test]
+    fn first_level_removal() {
+        let tolerance = Tolerance::default();
+        assert_eq!(tolerance.dampen(&[9, 1, 2, 3]), Some(vec![0]));
+        assert_eq!(tolerance.dampen(&[1, 2, 3, 9]), Some(vec![3]));
+        assert_eq!(tolerance.dampen(&[3, 1, 2, 3, 4]), Some(vec![0]));
+    }
+
+    #[test]
+    fn custom_tolerance() {
+        let report = [1, 2, 9, 3, 10, 4];
+
+        assert_eq!(Tolerance::default().dampen(&report), None);
+
+        let two_removals = Tolerance {
+            removals: 2,
+            ..Default::default()
+        };
+        assert_eq!(two_removals.dampen(&report), Some(vec![2, 4]));
+
+        let wide_steps = Tolerance {
+            step: 1..=7,
+            removals: 0,
+            ..Default::default()
+        };
+        assert_eq!(wide_steps.dampen(&[1, 8, 9]), Some(vec![]));
+
+        let loose = Tolerance {
+            strict: false,
+            removals: 0,
+            ..Default::default()
+        };
+        assert_eq!(loose.dampen(&[1, 3, 2, 4]), Some(vec![]));
+        assert_eq!(Tolerance::default().dampen(&[1, 3, 2, 4]), Some(vec![1]));
+    }
 }
@@ -0,0 +1,251 @@
+use core::panic;
+use std::{collections::BTreeMap, str::FromStr};
+
+const INPUT: &str = include_str!("../input.txt");
+
+#[derive(Clone, Copy, Debug)]
+enum Direction {
+    Up,
+    Down,
+    Right,
+    Left,
+}
+
+impl Direction {
+    const DIRECTIONS: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    fn delta(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Position {
+    Wall,
+    Track,
+}
+
+struct RaceTrack {
+    map: Vec<Vec<Position>>,
+    size: (usize, usize),
+    start: (usize, usize),
+    end: (usize, usize),
+    track: Vec<(usize, usize)>,
+}
+
+impl FromStr for RaceTrack {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let map: Vec<Vec<_>> = s
+            .lines()
+            .map(|l| {
+                l.chars()
+                    .map(|c| match c {
+                        '#' => Position::Wall,
+                        '.' | 'E' | 'S' => Position::Track,
+                        _ => panic!("invalid map string"),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut start = (0, 0);
+        let mut end = (0, 0);
+        for (i, l) in s.lines().enumerate() {
+            for (j, c) in l.chars().enumerate() {
+                if c == 'S' {
+                    start = (i, j);
+                } else if c == 'E' {
+                    end = (i, j);
+                }
+            }
+        }
+
+        Ok(RaceTrack {
+            size: (map.len(), map[0].len()),
+            map,
+            start,
+            end,
+            track: vec![],
+        })
+    }
+}
+
+impl RaceTrack {
+    fn mesure_track(&mut self) -> Vec<Vec<Option<usize>>> {
+        let mut pos = self.start;
+        let mut cost = 0;
+        let mut visited = vec![vec![None; self.map[0].len()]; self.map.len()];
+
+        while pos != self.end {
+            self.track.push(pos);
+            visited[pos.0][pos.1] = Some(cost);
+
+            for dir in Direction::DIRECTIONS {
+                let new_pos = self.increment_pos(pos, dir.delta()).unwrap();
+                if matches!(self.map[new_pos.0][new_pos.1], Position::Track)
+                    && visited[new_pos.0][new_pos.1].is_none()
+                {
+                    pos = new_pos;
+                    break;
+                }
+            }
+
+            cost += 1;
+        }
+        visited[self.end.0][self.end.1] = Some(cost);
+        self.track.push(pos);
+
+        visited
+    }
+
+    /// Counts cheats by the number of picoseconds they save. A cheat can end on
+    /// any track cell within `cheat_time` steps of its start, walls or not, so
+    /// each track cell only scans the diamond of that radius around it.
+    fn savings(&mut self, cheat_time: usize) -> BTreeMap<usize, usize> {
+        let visited = self.mesure_track();
+        let radius = cheat_time as i32;
+
+        let mut savings = BTreeMap::new();
+        for &pos in &self.track {
+            let cost_start = visited[pos.0][pos.1].unwrap();
+            for di in -radius..=radius {
+                let width = radius - di.abs();
+                for dj in -width..=width {
+                    let Some(end) = self.increment_pos(pos, (di, dj)) else {
+                        continue;
+                    };
+                    let Some(cost_end) = visited[end.0][end.1] else {
+                        continue;
+                    };
+
+                    let duration = (di.abs() + dj.abs()) as usize;
+                    let saved = cost_end.saturating_sub(cost_start + duration);
+                    if saved > 0 {
+                        *savings.entry(saved).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        savings
+    }
+
+    fn increment_pos(&self, pos: (usize, usize), delta: (i32, i32)) -> Option<(usize, usize)> {
+        let new_pos = (
+            (pos.0 as i32).checked_add(delta.0)? as usize,
+            (pos.1 as i32).checked_add(delta.1)? as usize,
+        );
+
+        if new_pos.0 >= self.size.0 || new_pos.1 >= self.size.1 {
+            None
+        } else {
+            Some(new_pos)
+        }
+    }
+}
+
+fn histogram(savings: &BTreeMap<usize, usize>, threshold: usize) -> String {
+    savings
+        .range(threshold..)
+        .map(|(saved, count)| match count {
+            1 => format!("There is one cheat that saves {} picoseconds.", saved),
+            _ => format!(
+                "There are {} cheats that save {} picoseconds.",
+                count, saved
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, params @ ..] if command == "histogram" => {
+            let cheat_time = params.first().map_or(20, |s| s.parse().unwrap());
+            let threshold = params.get(1).map_or(100, |s| s.parse().unwrap());
+
+            let mut track = RaceTrack::from_str(INPUT).unwrap();
+            println!("{}", histogram(&track.savings(cheat_time), threshold));
+        }
+        _ => println!("Answer: {}", process(INPUT, 20, 100)),
+    }
+}
+
+fn process(input: &str, cheat_time: usize, threshold: usize) -> usize {
+    let mut track = RaceTrack::from_str(input).unwrap();
+    track
+        .savings(cheat_time)
+        .range(threshold..)
+        .map(|(_, count)| count)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############"#;
+
+    #[test]
+    fn example() {
+        assert_eq!(process(EXAMPLE, 20, 76), 3);
+        assert_eq!(process(EXAMPLE, 20, 50), 285);
+    }
+
+    #[test]
+    fn short_cheats() {
+        assert_eq!(process(EXAMPLE, 2, 12), 8);
+
+        let mut track = RaceTrack::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            histogram(&track.savings(2), 0),
+            r#"There are 14 cheats that save 2 picoseconds.
+There are 14 cheats that save 4 picoseconds.
+There are 2 cheats that save 6 picoseconds.
+There are 4 cheats that save 8 picoseconds.
+There are 2 cheats that save 10 picoseconds.
+There are 3 cheats that save 12 picoseconds.
+There is one cheat that saves 20 picoseconds.
+There is one cheat that saves 36 picoseconds.
+There is one cheat that saves 38 picoseconds.
+There is one cheat that saves 40 picoseconds.
+There is one cheat that saves 64 picoseconds."#
+        );
+    }
+
+    #[test]
+    fn long_cheats() {
+        let mut track = RaceTrack::from_str(EXAMPLE).unwrap();
+        let savings = track.savings(20);
+        assert_eq!(savings[&50], 32);
+        assert_eq!(savings[&72], 22);
+        assert_eq!(savings[&76], 3);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, str::FromStr};
+
+const INPUT: &str = include_str!("../input.txt");
+
+const NUMERIC: &str = "789\n456\n123\n 0A";
+const DIRECTIONAL: &str = " ^A\n<v>";
+
+/// Keypad layout, the gap is the blank cell robot arms must never point at.
+struct Grid {
+    keys: HashMap<char, (i32, i32)>,
+    gap: (i32, i32),
+}
+
+impl FromStr for Grid {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = HashMap::new();
+        let mut gap = Err(());
+        for (i, l) in s.lines().enumerate() {
+            for (j, c) in l.chars().enumerate() {
+                if c == ' ' {
+                    gap = Ok((i as i32, j as i32));
+                } else {
+                    keys.insert(c, (i as i32, j as i32));
+                }
+            }
+        }
+
+        Ok(Grid { keys, gap: gap? })
+    }
+}
+
+impl Grid {
+    /// Directional key presses moving the arm from `from` to `to` then pressing
+    /// it. Only the two paths turning once can be optimal, zigzags cost more
+    /// presses on the keypad above.
+    fn paths(&self, from: char, to: char) -> Vec<String> {
+        let from = self.keys[&from];
+        let to = self.keys[&to];
+
+        let vertical = if to.0 > from.0 { "v" } else { "^" }.repeat(from.0.abs_diff(to.0) as usize);
+        let horizontal =
+            if to.1 > from.1 { ">" } else { "<" }.repeat(from.1.abs_diff(to.1) as usize);
+
+        let mut paths = vec![];
+        if (from.0, to.1) != self.gap {
+            paths.push(format!("{}{}A", horizontal, vertical));
+        }
+        if (to.0, from.1) != self.gap {
+            paths.push(format!("{}{}A", vertical, horizontal));
+        }
+        paths.dedup();
+        paths
+    }
+}
+
+struct Keypads {
+    numeric: Grid,
+    directional: Grid,
+    robots: usize,
+    memo: HashMap<(char, char, usize), u64>,
+}
+
+impl Keypads {
+    fn new(robots: usize) -> Self {
+        Keypads {
+            numeric: NUMERIC.parse().unwrap(),
+            directional: DIRECTIONAL.parse().unwrap(),
+            robots,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// Number of presses the human makes for the code to be typed on the
+    /// numeric keypad through all the robots.
+    fn code_cost(&mut self, code: &str) -> u64 {
+        let mut cost = 0;
+        let mut from = 'A';
+        for to in code.chars() {
+            cost += self
+                .numeric
+                .paths(from, to)
+                .iter()
+                .map(|path| self.sequence_cost(path, self.robots))
+                .min()
+                .unwrap();
+            from = to;
+        }
+        cost
+    }
+
+    // Every sequence starts with the arm on `A`, and ends there since it has to
+    // press the key on the keypad below.
+    fn sequence_cost(&mut self, sequence: &str, layers: usize) -> u64 {
+        let mut cost = 0;
+        let mut from = 'A';
+        for to in sequence.chars() {
+            cost += self.move_cost(from, to, layers);
+            from = to;
+        }
+        cost
+    }
+
+    fn move_cost(&mut self, from: char, to: char, layers: usize) -> u64 {
+        if layers == 0 {
+            return 1;
+        }
+        if let Some(cost) = self.memo.get(&(from, to, layers)) {
+            return *cost;
+        }
+
+        let cost = self
+            .directional
+            .paths(from, to)
+            .iter()
+            .map(|path| self.sequence_cost(path, layers - 1))
+            .min()
+            .unwrap();
+        self.memo.insert((from, to, layers), cost);
+        cost
+    }
+}
+
+fn main() {
+    println!("Answer: {}", process(INPUT, 2));
+}
+
+fn process(input: &str, robots: usize) -> u64 {
+    let mut keypads = Keypads::new(robots);
+    input
+        .lines()
+        .map(|code| {
+            let value: u64 = code.trim_end_matches('A').parse().unwrap();
+            keypads.code_cost(code) * value
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"029A
+980A
+179A
+456A
+379A"#;
+
+    #[test]
+    fn example() {
+        assert_eq!(process(EXAMPLE, 2), 126384)
+    }
+
+    #[test]
+    fn code_costs() {
+        let mut keypads = Keypads::new(2);
+        let costs: Vec<u64> = EXAMPLE.lines().map(|c| keypads.code_cost(c)).collect();
+        assert_eq!(costs, vec![68, 60, 68, 64, 64]);
+    }
+
+    #[test]
+    fn robot_layers() {
+        // <A^A>^^AvvvA
+        assert_eq!(Keypads::new(0).code_cost("029A"), 12);
+        // v<<A>>^A<A>AvA<^AA>A<vAAA>^A
+        assert_eq!(Keypads::new(1).code_cost("029A"), 28);
+    }
+
+    #[test]
+    fn gap() {
+        let numeric: Grid = NUMERIC.parse().unwrap();
+        assert_eq!(numeric.paths('A', '1'), vec!["^<<A"]);
+        assert_eq!(numeric.paths('7', '0'), vec![">vvvA"]);
+
+        let directional: Grid = DIRECTIONAL.parse().unwrap();
+        assert_eq!(directional.paths('<', 'A'), vec![">>^A"]);
+        assert_eq!(directional.paths('A', 'v'), vec!["<vA", "v<A"]);
+    }
+}
@@ -0,0 +1,66 @@
+const INPUT: &str = include_str!("../input.txt");
+
+const PRUNE: u64 = 16777216;
+
+/// Successive secret numbers of a buyer, starting with the initial one.
+struct Secrets {
+    secret: u64,
+}
+
+impl Secrets {
+    fn new(secret: u64) -> Self {
+        Secrets { secret }
+    }
+}
+
+impl Iterator for Secrets {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.secret;
+
+        let mut secret = current;
+        secret = ((secret << 6) ^ secret) % PRUNE;
+        secret = ((secret >> 5) ^ secret) % PRUNE;
+        secret = ((secret << 11) ^ secret) % PRUNE;
+        self.secret = secret;
+
+        Some(current)
+    }
+}
+
+fn main() {
+    println!("Answer: {}", process(INPUT));
+}
+
+fn process(input: &str) -> u64 {
+    input
+        .lines()
+        .map(|l| Secrets::new(l.parse().unwrap()).nth(2000).unwrap())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = r#"1
+10
+100
+2024"#;
+        assert_eq!(process(input), 37327623)
+    }
+
+    #[test]
+    fn secrets() {
+        assert_eq!(
+            Secrets::new(123).skip(1).take(10).collect::<Vec<_>>(),
+            vec![
+                15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+                5908254
+            ]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+const INPUT: &str = include_str!("../input.txt");
+
+const PRUNE: u64 = 16777216;
+
+/// Successive secret numbers of a buyer, starting with the initial one.
+struct Secrets {
+    secret: u64,
+}
+
+impl Secrets {
+    fn new(secret: u64) -> Self {
+        Secrets { secret }
+    }
+}
+
+impl Iterator for Secrets {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.secret;
+
+        let mut secret = current;
+        secret = ((secret << 6) ^ secret) % PRUNE;
+        secret = ((secret >> 5) ^ secret) % PRUNE;
+        secret = ((secret << 11) ^ secret) % PRUNE;
+        self.secret = secret;
+
+        Some(current)
+    }
+}
+
+// Four deltas between -9 and 9, stored as base 19 digits
+const NB_SEQUENCES: usize = 19 * 19 * 19 * 19;
+
+fn decode(index: usize) -> [i8; 4] {
+    let mut deltas = [0; 4];
+    for (i, delta) in deltas.iter_mut().enumerate() {
+        *delta = (index / 19usize.pow(3 - i as u32) % 19) as i8 - 9;
+    }
+    deltas
+}
+
+/// Sequence of four price changes selling the most bananas, and how many.
+fn best_sequence(secrets: &[u64]) -> ([i8; 4], u32) {
+    let mut bananas = vec![0u32; NB_SEQUENCES];
+    // Monkeys sell at the first occurrence of the sequence only
+    let mut seen = vec![usize::MAX; NB_SEQUENCES];
+
+    for (buyer, &secret) in secrets.iter().enumerate() {
+        let prices: Vec<i8> = Secrets::new(secret)
+            .take(2001)
+            .map(|s| (s % 10) as i8)
+            .collect();
+
+        let mut index = 0;
+        for (i, w) in prices.windows(2).enumerate() {
+            index = (index * 19 + (w[1] - w[0] + 9) as usize) % NB_SEQUENCES;
+            if i >= 3 && seen[index] != buyer {
+                seen[index] = buyer;
+                bananas[index] += w[1] as u32;
+            }
+        }
+    }
+
+    let (index, total) = bananas
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, total)| **total)
+        .unwrap();
+    (decode(index), *total)
+}
+
+fn main() {
+    println!("Answer: {}", process(INPUT));
+}
+
+fn process(input: &str) -> u32 {
+    let secrets: Vec<u64> = input.lines().map(|l| l.parse().unwrap()).collect();
+    best_sequence(&secrets).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = r#"1
+2
+3
+2024"#;
+        assert_eq!(process(input), 23)
+    }
+
+    #[test]
+    fn best_changes() {
+        assert_eq!(best_sequence(&[1, 2, 3, 2024]), ([-2, 1, -1, 3], 23));
+        assert_eq!(decode(0), [-9, -9, -9, -9]);
+        assert_eq!(decode(NB_SEQUENCES - 1), [9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn prices() {
+        let prices: Vec<u64> = Secrets::new(123).take(10).map(|s| s % 10).collect();
+        assert_eq!(prices, vec![3, 0, 6, 5, 4, 4, 6, 4, 4, 2]);
+    }
+}
@@ -0,0 +1,65 @@
+use graph::Graph;
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn main() {
+    println!("Answer: {}", process(INPUT));
+}
+
+fn process(input: &str) -> usize {
+    let graph: Graph = input.parse().unwrap();
+    graph
+        .triangles()
+        .iter()
+        .filter(|t| t.iter().any(|&n| graph.name(n).starts_with('t')))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn"#;
+
+    #[test]
+    fn example() {
+        assert_eq!(process(EXAMPLE), 7)
+    }
+
+    #[test]
+    fn triangles() {
+        let graph: Graph = EXAMPLE.parse().unwrap();
+        assert_eq!(graph.triangles().len(), 12);
+    }
+}
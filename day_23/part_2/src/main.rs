@@ -0,0 +1,61 @@
+use graph::Graph;
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn main() {
+    println!("Answer: {}", process(INPUT));
+}
+
+fn process(input: &str) -> String {
+    let graph: Graph = input.parse().unwrap();
+
+    let mut names: Vec<&str> = graph
+        .maximum_clique()
+        .iter()
+        .map(|&n| graph.name(n))
+        .collect();
+    names.sort();
+    names.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = r#"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn"#;
+        assert_eq!(process(input), "co,de,ka,ta")
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+};
+
+const INPUT: &str = include_str!("../input.txt");
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl FromStr for Op {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Op {
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a && b,
+            Op::Or => a || b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Gate {
+    a: String,
+    b: String,
+    op: Op,
+    out: String,
+}
+
+impl FromStr for Gate {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts[..] {
+            [a, op, b, "->", out] => Ok(Gate {
+                a: a.to_string(),
+                b: b.to_string(),
+                op: op.parse()?,
+                out: out.to_string(),
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+struct Circuit {
+    inputs: HashMap<String, bool>,
+    gates: Vec<Gate>,
+}
+
+impl FromStr for Circuit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (inputs, gates) = s.split_once("\n\n").ok_or(())?;
+
+        let inputs = inputs
+            .lines()
+            .map(|l| {
+                let (wire, value) = l.split_once(": ").ok_or(())?;
+                Ok((wire.to_string(), value == "1"))
+            })
+            .collect::<Result<_, ()>>()?;
+        let gates = gates.lines().map(|l| l.parse()).collect::<Result<_, _>>()?;
+
+        Ok(Circuit { inputs, gates })
+    }
+}
+
+impl Circuit {
+    /// Gates in an order where each one comes after the gates driving its
+    /// inputs, `None` when the wires loop.
+    fn order(&self) -> Option<Vec<usize>> {
+        let drivers: HashMap<&str, usize> = self
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (g.out.as_str(), i))
+            .collect();
+
+        let mut pending = vec![0; self.gates.len()];
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; self.gates.len()];
+        for (i, gate) in self.gates.iter().enumerate() {
+            for wire in [&gate.a, &gate.b] {
+                if let Some(&driver) = drivers.get(wire.as_str()) {
+                    pending[i] += 1;
+                    dependents[driver].push(i);
+                }
+            }
+        }
+
+        let mut queue: VecDeque<usize> =
+            (0..self.gates.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = vec![];
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            for &dependent in &dependents[i] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        (order.len() == self.gates.len()).then_some(order)
+    }
+
+    fn simulate(&self) -> HashMap<String, bool> {
+        let mut values = self.inputs.clone();
+        for i in self.order().expect("wires loop") {
+            let gate = &self.gates[i];
+            let value = gate.op.apply(values[&gate.a], values[&gate.b]);
+            values.insert(gate.out.clone(), value);
+        }
+        values
+    }
+}
+
+/// Bit of the number formed by the wires starting with `prefix`, `None` for
+/// wires not followed by a bit index, like `zab`.
+fn bit(wire: &str, prefix: char) -> Option<u32> {
+    let index = wire.strip_prefix(prefix)?;
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// Number formed by the wires starting with `prefix`, `00` being the lowest bit.
+fn number(values: &HashMap<String, bool>, prefix: char) -> u64 {
+    values
+        .iter()
+        .filter(|(_, value)| **value)
+        .filter_map(|(wire, _)| bit(wire, prefix))
+        .filter_map(|bit| 1u64.checked_shl(bit))
+        .sum()
+}
+
+fn main() {
+    println!("Answer: {}", process(INPUT));
+}
+
+fn process(input: &str) -> u64 {
+    let circuit: Circuit = input.parse().unwrap();
+    number(&circuit.simulate(), 'z')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_example() {
+        let input = r#"x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02"#;
+        assert_eq!(process(input), 4)
+    }
+
+    #[test]
+    fn example() {
+        let input = r#"x00: 1
+x01: 0
+x02: 1
+x03: 1
+x04: 0
+y00: 1
+y01: 1
+y02: 1
+y03: 1
+y04: 1
+
+ntg XOR fgs -> mjb
+y02 OR x01 -> tnw
+kwq OR kpj -> z05
+x00 OR x03 -> fst
+tgd XOR rvg -> z01
+vdt OR tnw -> bfw
+bfw AND frj -> z10
+ffh OR nrd -> bqk
+y00 AND y03 -> djm
+y03 OR y00 -> psh
+bqk OR frj -> z08
+tnw OR fst -> frj
+gnj AND tgd -> z11
+bfw XOR mjb -> z00
+x03 OR x00 -> vdt
+gnj AND wpb -> z02
+x04 AND y00 -> kjc
+djm OR pbm -> qhw
+nrd AND vdt -> hwm
+kjc AND fst -> rvg
+y04 OR y02 -> fgs
+y01 AND x02 -> pbm
+ntg OR kjc -> kwq
+psh XOR fgs -> tgd
+qhw XOR tgd -> z09
+pbm OR djm -> kpj
+x03 XOR y03 -> ffh
+x00 XOR y04 -> ntg
+bfw OR bqk -> z06
+nrd XOR fgs -> wpb
+frj XOR qhw -> z04
+bqk OR frj -> z07
+y03 OR x01 -> nrd
+hwm AND bqk -> z03
+tgd XOR rvg -> z12
+tnw OR pbm -> gnj"#;
+        assert_eq!(process(input), 2024)
+    }
+
+    #[test]
+    fn loops() {
+        let circuit: Circuit = "x00: 1\n\nx00 AND b -> a\nx00 OR a -> b".parse().unwrap();
+        assert_eq!(circuit.order(), None);
+    }
+
+    #[test]
+    fn unnumbered_wires() {
+        let input = "x00: 1\ny00: 1\n\nx00 AND y00 -> zab\nx00 XOR y00 -> z00\nx00 OR y00 -> z";
+        assert_eq!(process(input), 0);
+    }
+}
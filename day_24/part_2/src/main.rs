@@ -0,0 +1,379 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+};
+
+const INPUT: &str = include_str!("../input.txt");
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl FromStr for Op {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Op {
+    fn name(&self) -> &str {
+        match self {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Xor => "XOR",
+        }
+    }
+
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a && b,
+            Op::Or => a || b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Gate {
+    a: String,
+    b: String,
+    op: Op,
+    out: String,
+}
+
+impl FromStr for Gate {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts[..] {
+            [a, op, b, "->", out] => Ok(Gate {
+                a: a.to_string(),
+                b: b.to_string(),
+                op: op.parse()?,
+                out: out.to_string(),
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+struct Circuit {
+    inputs: HashMap<String, bool>,
+    gates: Vec<Gate>,
+}
+
+impl FromStr for Circuit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (inputs, gates) = s.split_once("\n\n").ok_or(())?;
+
+        let inputs = inputs
+            .lines()
+            .map(|l| {
+                let (wire, value) = l.split_once(": ").ok_or(())?;
+                Ok((wire.to_string(), value == "1"))
+            })
+            .collect::<Result<_, ()>>()?;
+        let gates = gates.lines().map(|l| l.parse()).collect::<Result<_, _>>()?;
+
+        Ok(Circuit { inputs, gates })
+    }
+}
+
+impl Circuit {
+    /// Gates in an order where each one comes after the gates driving its
+    /// inputs, `None` when the wires loop.
+    fn order(&self) -> Option<Vec<usize>> {
+        let drivers: HashMap<&str, usize> = self
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (g.out.as_str(), i))
+            .collect();
+
+        let mut pending = vec![0; self.gates.len()];
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; self.gates.len()];
+        for (i, gate) in self.gates.iter().enumerate() {
+            for wire in [&gate.a, &gate.b] {
+                if let Some(&driver) = drivers.get(wire.as_str()) {
+                    pending[i] += 1;
+                    dependents[driver].push(i);
+                }
+            }
+        }
+
+        let mut queue: VecDeque<usize> =
+            (0..self.gates.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = vec![];
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            for &dependent in &dependents[i] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        (order.len() == self.gates.len()).then_some(order)
+    }
+
+    fn simulate(&self) -> HashMap<String, bool> {
+        let mut values = self.inputs.clone();
+        for i in self.order().expect("wires loop") {
+            let gate = &self.gates[i];
+            let value = gate.op.apply(values[&gate.a], values[&gate.b]);
+            values.insert(gate.out.clone(), value);
+        }
+        values
+    }
+}
+
+impl Circuit {
+    /// Gate outputs that break the structure of a ripple-carry adder, where
+    /// each bit adds `x` and `y` with two XOR gates and carries over with two
+    /// AND gates joined by an OR gate, the first bit being a half adder.
+    fn swapped_outputs(&self) -> Vec<String> {
+        let Some(last_z) = self
+            .gates
+            .iter()
+            .map(|g| &g.out)
+            .filter(|out| bit(out, 'z').is_some())
+            .max_by_key(|out| bit(out, 'z'))
+        else {
+            return vec![];
+        };
+        let feeds = |wire: &str, op: Op| {
+            self.gates
+                .iter()
+                .any(|g| g.op == op && (g.a == wire || g.b == wire))
+        };
+        let is_input = |wire: &str| wire.starts_with('x') || wire.starts_with('y');
+
+        let mut swapped: Vec<String> = self
+            .gates
+            .iter()
+            .filter(|g| {
+                let from_inputs = is_input(&g.a) && is_input(&g.b);
+                let first_bit = from_inputs && g.a.ends_with("00");
+
+                match g.op {
+                    _ if g.out == *last_z => g.op != Op::Or,
+                    // Output bits other than the first add the carry to the
+                    // XOR of their inputs, never the inputs themselves
+                    _ if bit(&g.out, 'z').is_some() => {
+                        g.op != Op::Xor || (from_inputs && !first_bit)
+                    }
+                    Op::Xor if !from_inputs => true,
+                    Op::Xor => !first_bit && !feeds(&g.out, Op::Xor),
+                    Op::And => !first_bit && !feeds(&g.out, Op::Or),
+                    Op::Or => false,
+                }
+            })
+            .map(|g| g.out.clone())
+            .collect();
+        swapped.sort();
+        swapped
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = vec!["digraph circuit {".to_string()];
+        for gate in &self.gates {
+            dot.push(format!(
+                "    {} [label=\"{} {}\"];",
+                gate.out,
+                gate.out,
+                gate.op.name()
+            ));
+            dot.push(format!("    {} -> {};", gate.a, gate.out));
+            dot.push(format!("    {} -> {};", gate.b, gate.out));
+        }
+        dot.push("}".to_string());
+        dot.join("\n")
+    }
+}
+
+/// Bit of the number formed by the wires starting with `prefix`, `None` for
+/// wires not followed by a bit index, like `zab`.
+fn bit(wire: &str, prefix: char) -> Option<u32> {
+    let index = wire.strip_prefix(prefix)?;
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// Number formed by the wires starting with `prefix`, `00` being the lowest bit.
+fn number(values: &HashMap<String, bool>, prefix: char) -> u64 {
+    values
+        .iter()
+        .filter(|(_, value)| **value)
+        .filter_map(|(wire, _)| bit(wire, prefix))
+        .filter_map(|bit| 1u64.checked_shl(bit))
+        .sum()
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("dot") => println!("{}", INPUT.parse::<Circuit>().unwrap().to_dot()),
+        Some("check") => {
+            let values = INPUT.parse::<Circuit>().unwrap().simulate();
+            let (x, y, z) = (
+                number(&values, 'x'),
+                number(&values, 'y'),
+                number(&values, 'z'),
+            );
+            println!(
+                "{} + {} = {} ({})",
+                x,
+                y,
+                z,
+                if x + y == z { "ok" } else { "wrong" }
+            );
+        }
+        _ => println!("Answer: {}", process(INPUT)),
+    }
+}
+
+fn process(input: &str) -> String {
+    let circuit: Circuit = input.parse().unwrap();
+    circuit.swapped_outputs().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ripple-carry adder over `bits` bits, with gate outputs named after their
+    // role so that tests can swap them.
+    fn adder(bits: usize, x: u64, y: u64) -> String {
+        let mut lines = vec![];
+        for i in 0..bits {
+            lines.push(format!("x{:02}: {}", i, x >> i & 1));
+        }
+        for i in 0..bits {
+            lines.push(format!("y{:02}: {}", i, y >> i & 1));
+        }
+        lines.push(String::new());
+
+        let carry = |i: usize| {
+            if i + 1 == bits {
+                format!("z{:02}", bits)
+            } else {
+                format!("c{:02}", i)
+            }
+        };
+        lines.push("x00 XOR y00 -> z00".to_string());
+        lines.push(format!("x00 AND y00 -> {}", carry(0)));
+        for i in 1..bits {
+            lines.push(format!("x{i:02} XOR y{i:02} -> a{i:02}"));
+            lines.push(format!("x{i:02} AND y{i:02} -> b{i:02}"));
+            lines.push(format!("a{i:02} XOR {} -> z{i:02}", carry(i - 1)));
+            lines.push(format!("a{i:02} AND {} -> d{i:02}", carry(i - 1)));
+            lines.push(format!("b{i:02} OR d{i:02} -> {}", carry(i)));
+        }
+        lines.join("\n")
+    }
+
+    fn swap(circuit: &str, a: &str, b: &str) -> String {
+        circuit
+            .replace(&format!("-> {}", a), "-> SWAP")
+            .replace(&format!("-> {}", b), &format!("-> {}", a))
+            .replace("-> SWAP", &format!("-> {}", b))
+    }
+
+    #[test]
+    fn adder_structure() {
+        let circuit: Circuit = adder(4, 11, 6).parse().unwrap();
+        let values = circuit.simulate();
+        assert_eq!(number(&values, 'z'), 17);
+        assert!(circuit.swapped_outputs().is_empty());
+    }
+
+    #[test]
+    fn swapped() {
+        let input = swap(&adder(4, 11, 6), "z01", "d01");
+        let input = swap(&input, "a02", "b02");
+        let circuit: Circuit = input.parse().unwrap();
+        assert_ne!(number(&circuit.simulate(), 'z'), 17);
+
+        assert_eq!(process(&input), "a02,b02,d01,z01");
+
+        let input = swap(&adder(4, 11, 6), "z02", "a02");
+        assert_eq!(process(&input), "a02,z02");
+
+        // Outputs are swapped in pairs
+        for (a, b) in [
+            ("z01", "d01"),
+            ("a02", "b02"),
+            ("z02", "a02"),
+            ("z03", "c02"),
+        ] {
+            let circuit: Circuit = swap(&adder(4, 11, 6), a, b).parse().unwrap();
+            assert_eq!(circuit.swapped_outputs().len() % 2, 0, "{} <-> {}", a, b);
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let circuit: Circuit = adder(2, 0, 0).parse().unwrap();
+        assert_eq!(
+            circuit.to_dot(),
+            r#"digraph circuit {
+    z00 [label="z00 XOR"];
+    x00 -> z00;
+    y00 -> z00;
+    c00 [label="c00 AND"];
+    x00 -> c00;
+    y00 -> c00;
+    a01 [label="a01 XOR"];
+    x01 -> a01;
+    y01 -> a01;
+    b01 [label="b01 AND"];
+    x01 -> b01;
+    y01 -> b01;
+    z01 [label="z01 XOR"];
+    a01 -> z01;
+    c00 -> z01;
+    d01 [label="d01 AND"];
+    a01 -> d01;
+    c00 -> d01;
+    z02 [label="z02 OR"];
+    b01 -> z02;
+    d01 -> z02;
+}"#
+        );
+    }
+
+    #[test]
+    fn loops() {
+        let circuit: Circuit = "x00: 1\n\nx00 AND b -> a\nx00 OR a -> b".parse().unwrap();
+        assert_eq!(circuit.order(), None);
+    }
+
+    #[test]
+    fn unnumbered_wires() {
+        let circuit: Circuit = "x00: 1\ny00: 1\n\nx00 AND y00 -> zab\nx00 OR y00 -> c"
+            .parse()
+            .unwrap();
+        assert_eq!(number(&circuit.simulate(), 'z'), 0);
+        assert!(circuit.swapped_outputs().is_empty());
+
+        let input = adder(2, 1, 1) + "\nx00 AND y00 -> zab";
+        let circuit: Circuit = input.parse().unwrap();
+        assert_eq!(number(&circuit.simulate(), 'z'), 2);
+        assert!(circuit.swapped_outputs().is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+use std::str::FromStr;
+
+const INPUT: &str = include_str!("../input.txt");
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Lock,
+    Key,
+}
+
+#[derive(Debug, PartialEq)]
+struct Schematic {
+    kind: Kind,
+    heights: Vec<usize>,
+    // Rows between the full top and bottom rows, shared by a lock and its key
+    space: usize,
+}
+
+impl FromStr for Schematic {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&[u8]> = s.lines().map(|l| l.as_bytes()).collect();
+        let width = rows.first().ok_or(())?.len();
+        if rows.len() < 2 || rows.iter().any(|r| r.len() != width) {
+            return Err(());
+        }
+
+        let full = |row: &[u8]| row.iter().all(|c| *c == b'#');
+        let kind = match (full(rows[0]), full(rows[rows.len() - 1])) {
+            (true, false) => Kind::Lock,
+            (false, true) => Kind::Key,
+            _ => return Err(()),
+        };
+
+        let heights = (0..width)
+            .map(|j| rows.iter().filter(|r| r[j] == b'#').count() - 1)
+            .collect();
+
+        Ok(Schematic {
+            kind,
+            heights,
+            space: rows.len() - 2,
+        })
+    }
+}
+
+impl Schematic {
+    fn fits(&self, key: &Schematic) -> bool {
+        self.space == key.space
+            && self.heights.len() == key.heights.len()
+            && self
+                .heights
+                .iter()
+                .zip(&key.heights)
+                .all(|(l, k)| l + k <= self.space)
+    }
+}
+
+fn parse(input: &str) -> (Vec<Schematic>, Vec<Schematic>) {
+    input
+        .split("\n\n")
+        .map(|s| s.parse::<Schematic>().unwrap())
+        .partition(|s| s.kind == Kind::Lock)
+}
+
+/// Indices of the keys fitting each lock.
+fn fitting_keys(locks: &[Schematic], keys: &[Schematic]) -> Vec<Vec<usize>> {
+    locks
+        .iter()
+        .map(|lock| (0..keys.len()).filter(|&k| lock.fits(&keys[k])).collect())
+        .collect()
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("report") => {
+            let (locks, keys) = parse(INPUT);
+            for (lock, fitting) in locks.iter().zip(fitting_keys(&locks, &keys)) {
+                let fitting: Vec<&Vec<usize>> = fitting.iter().map(|&k| &keys[k].heights).collect();
+                println!("Lock {:?}: {:?}", lock.heights, fitting);
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT)),
+    }
+}
+
+fn process(input: &str) -> usize {
+    let (locks, keys) = parse(input);
+    fitting_keys(&locks, &keys).iter().map(|k| k.len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####"#;
+
+    #[test]
+    fn example() {
+        assert_eq!(process(EXAMPLE), 3)
+    }
+
+    #[test]
+    fn heights() {
+        let (locks, keys) = parse(EXAMPLE);
+        assert_eq!(
+            locks.iter().map(|l| l.heights.clone()).collect::<Vec<_>>(),
+            vec![vec![0, 5, 3, 4, 3], vec![1, 2, 0, 5, 3]]
+        );
+        assert_eq!(
+            keys.iter().map(|k| k.heights.clone()).collect::<Vec<_>>(),
+            vec![
+                vec![5, 0, 2, 1, 3],
+                vec![4, 3, 4, 0, 2],
+                vec![3, 0, 2, 0, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn fitting() {
+        let (locks, keys) = parse(EXAMPLE);
+        assert_eq!(fitting_keys(&locks, &keys), vec![vec![2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn other_sizes() {
+        let input = r#"###
+.#.
+...
+...
+
+...
+#..
+#.#
+###
+
+...
+...
+##.
+###"#;
+        let (locks, keys) = parse(input);
+        assert_eq!(locks[0].heights, vec![0, 1, 0]);
+        assert_eq!(keys[0].heights, vec![2, 0, 1]);
+        assert_eq!(fitting_keys(&locks, &keys), vec![vec![0, 1]]);
+
+        let wide: Schematic = "....\n....\n####".parse().unwrap();
+        assert!(!locks[0].fits(&wide));
+        assert!("####\n####".parse::<Schematic>().is_err());
+    }
+}
@@ -1,31 +1,255 @@
-use regex::Regex;
+use std::io::{self, BufReader, Bytes, Read};
 
 const INPUT: &str = include_str!("../input.txt");
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+const MAX_DIGITS: usize = 3;
+
+#[derive(Clone, Copy)]
+enum Effect {
+    Enable,
+    Disable,
+    Compute(fn(&[u32]) -> i64),
+}
+
+struct Instruction {
+    name: String,
+    arity: usize,
+    effect: Effect,
+}
+
+#[derive(Debug, PartialEq)]
+struct Ignored {
+    name: String,
+    offset: usize,
+    reason: String,
 }
 
-fn process(input: &str) -> i32 {
-    let re = Regex::new(r"mul\((\d*),(\d*)\)|()()do\(\)|()()don't\(\)").unwrap();
+#[derive(Debug, PartialEq)]
+enum Token {
+    Call {
+        name: String,
+        args: Vec<u32>,
+        offset: usize,
+    },
+    Rejected(Ignored),
+}
+
+// Reads the corrupted memory byte by byte and emits a token whenever the bytes
+// read so far end with the name of an instruction.
+struct Tokenizer<'a, R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    peeked: Option<u8>,
+    offset: usize,
+    history: Vec<u8>,
+    instructions: &'a [Instruction],
+}
+
+impl<'a, R: Read> Tokenizer<'a, R> {
+    fn new(reader: R, instructions: &'a [Instruction]) -> Self {
+        Tokenizer {
+            bytes: BufReader::new(reader).bytes(),
+            peeked: None,
+            offset: 0,
+            history: vec![],
+            instructions,
+        }
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        if self.peeked.is_none() {
+            self.peeked = self.bytes.next().transpose()?;
+        }
+        Ok(self.peeked)
+    }
+
+    fn read(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.peeked = None;
+            self.offset += 1;
+        }
+        Ok(byte)
+    }
+
+    fn matching_instruction(&self) -> Option<&'a Instruction> {
+        self.instructions
+            .iter()
+            .filter(|i| self.history.ends_with(i.name.as_bytes()))
+            .max_by_key(|i| i.name.len())
+    }
+
+    fn call(&mut self, instruction: &Instruction) -> io::Result<Token> {
+        let offset = self.offset - instruction.name.len();
+        let rejected = |reason: &str| {
+            Token::Rejected(Ignored {
+                name: instruction.name.clone(),
+                offset,
+                reason: reason.to_string(),
+            })
+        };
+
+        if self.peek()? != Some(b'(') {
+            return Ok(rejected("expected `(`"));
+        }
+        self.read()?;
+
+        let mut args = vec![];
+        for i in 0..instruction.arity {
+            let mut digits = String::new();
+            while let Some(byte) = self.peek()?.filter(|b| b.is_ascii_digit()) {
+                digits.push(byte as char);
+                self.read()?;
+            }
+            if digits.is_empty() {
+                return Ok(rejected("expected a number"));
+            }
+            if digits.len() > MAX_DIGITS {
+                return Ok(rejected("number with more than 3 digits"));
+            }
+            args.push(digits.parse().unwrap());
+
+            if i + 1 < instruction.arity {
+                if self.peek()? != Some(b',') {
+                    return Ok(rejected("expected `,`"));
+                }
+                self.read()?;
+            }
+        }
+
+        if self.peek()? != Some(b')') {
+            return Ok(rejected("expected `)`"));
+        }
+        self.read()?;
+
+        Ok(Token::Call {
+            name: instruction.name.clone(),
+            args,
+            offset,
+        })
+    }
+}
+
+impl<R: Read> Iterator for Tokenizer<'_, R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let longest_name = self.instructions.iter().map(|i| i.name.len()).max()?;
+
+        loop {
+            match self.read() {
+                Ok(Some(byte)) => self.history.push(byte),
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+            if self.history.len() > longest_name {
+                self.history.remove(0);
+            }
+
+            if let Some(instruction) = self.matching_instruction() {
+                // Instructions without arguments also appear inside other words,
+                // like `do` in `don't`, only report them when actually called.
+                if instruction.arity == 0 && !matches!(self.peek(), Ok(Some(b'('))) {
+                    continue;
+                }
+
+                self.history.clear();
+                return Some(self.call(instruction));
+            }
+        }
+    }
+}
 
-    let mut total = 0;
-    let mut do_next = true;
+// Kept apart from the instruction table, which the tokenizer borrows while the
+// state changes.
+struct State {
+    enabled: bool,
+    total: i64,
+    ignored: Vec<Ignored>,
+}
 
-    for (str, caps) in re.captures_iter(input).map(|c| c.extract()) {
-        match str {
-            "do()" => do_next = true,
-            "don't()" => do_next = false,
-            _ => {
-                if do_next {
-                    let [a, b] = caps;
-                    total += a.parse::<i32>().unwrap() * b.parse::<i32>().unwrap();
+impl State {
+    fn execute(&mut self, token: Token, instructions: &[Instruction]) {
+        match token {
+            Token::Call { name, args, offset } => {
+                let instruction = instructions.iter().find(|i| i.name == name).unwrap();
+                match instruction.effect {
+                    Effect::Enable => self.enabled = true,
+                    Effect::Disable => self.enabled = false,
+                    Effect::Compute(compute) if self.enabled => self.total += compute(&args),
+                    Effect::Compute(_) => self.ignored.push(Ignored {
+                        name,
+                        offset,
+                        reason: "disabled".to_string(),
+                    }),
                 }
             }
+            Token::Rejected(ignored) => self.ignored.push(ignored),
         }
     }
+}
+
+struct Interpreter {
+    instructions: Vec<Instruction>,
+    state: State,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        let mut interpreter = Interpreter {
+            instructions: vec![],
+            state: State {
+                enabled: true,
+                total: 0,
+                ignored: vec![],
+            },
+        };
+        interpreter
+            .register(
+                "mul",
+                2,
+                Effect::Compute(|args| args[0] as i64 * args[1] as i64),
+            )
+            .register("do", 0, Effect::Enable)
+            .register("don't", 0, Effect::Disable);
+        interpreter
+    }
+}
+
+impl Interpreter {
+    fn register(&mut self, name: &str, arity: usize, effect: Effect) -> &mut Self {
+        self.instructions.push(Instruction {
+            name: name.to_string(),
+            arity,
+            effect,
+        });
+        self
+    }
 
-    total
+    fn run(&mut self, reader: impl Read) -> io::Result<i64> {
+        for token in Tokenizer::new(reader, &self.instructions) {
+            self.state.execute(token?, &self.instructions);
+        }
+
+        Ok(self.state.total)
+    }
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("ignored") => {
+            let mut interpreter = Interpreter::default();
+            interpreter.run(INPUT.as_bytes()).unwrap();
+
+            for ignored in &interpreter.state.ignored {
+                println!("{} at {}: {}", ignored.name, ignored.offset, ignored.reason);
+            }
+        }
+        _ => println!("Answer: {}", process(INPUT)),
+    }
+}
+
+fn process(input: &str) -> i64 {
+    Interpreter::default().run(input.as_bytes()).unwrap()
 }
 
 #[cfg(test)]
@@ -37,4 +261,64 @@ mod tests {
         let input = r#"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"#;
         assert_eq!(process(input), 48)
     }
+
+    #[test]
+    fn ignored() {
+        let input = r#"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"#;
+        let mut interpreter = Interpreter::default();
+        interpreter.run(input.as_bytes()).unwrap();
+
+        let ignored: Vec<(usize, &str)> = interpreter
+            .state
+            .ignored
+            .iter()
+            .map(|i| (i.offset, i.reason.as_str()))
+            .collect();
+        assert_eq!(
+            ignored,
+            vec![
+                (10, "expected `(`"),
+                (28, "disabled"),
+                (37, "expected `)`"),
+                (48, "disabled"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_instructions() {
+        let input = "mul(1234,5)add(2,3)mul(2,3)don't()add(1,1)";
+        let mut interpreter = Interpreter::default();
+        interpreter.register("add", 2, Effect::Compute(|args| (args[0] + args[1]) as i64));
+
+        assert_eq!(interpreter.run(input.as_bytes()).unwrap(), 11);
+        assert_eq!(
+            interpreter.state.ignored[0],
+            Ignored {
+                name: "mul".to_string(),
+                offset: 0,
+                reason: "number with more than 3 digits".to_string()
+            }
+        );
+        assert_eq!(interpreter.state.ignored.len(), 2);
+    }
+
+    #[test]
+    fn streaming() {
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+
+        // Instructions read before the error are already executed
+        let mut interpreter = Interpreter::default();
+        let reader = "mul(2,4)don't()mul(1,1)".as_bytes().chain(Broken);
+        assert!(interpreter.run(reader).is_err());
+        assert_eq!(interpreter.state.total, 8);
+        assert!(!interpreter.state.enabled);
+        assert_eq!(interpreter.state.ignored.len(), 1);
+    }
 }
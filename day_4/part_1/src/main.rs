@@ -1,65 +1,178 @@
+use std::{collections::HashSet, str::FromStr};
+
 const INPUT: &str = include_str!("../input.txt");
 
-const LETTERS: [char; 3] = ['M', 'A', 'S'];
-const DIRECTIONS: [(isize, isize); 8] = [
-    (0, 1),
-    (0, -1),
-    (1, 0),
-    (-1, 0),
-    (1, 1),
-    (1, -1),
-    (-1, 1),
-    (-1, -1),
-];
+type Match = Vec<(usize, usize)>;
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+// Rectangular template where `None` cells match any letter.
+#[derive(Clone, Debug, PartialEq)]
+struct Pattern {
+    cells: Vec<Vec<Option<char>>>,
 }
 
-fn process(input: &str) -> usize {
-    let word_search = input
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect::<Vec<Vec<char>>>();
+impl FromStr for Pattern {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let width = s.lines().map(|l| l.chars().count()).max().ok_or(())?;
+        let cells = s
+            .lines()
+            .map(|l| {
+                let mut row: Vec<Option<char>> = l
+                    .chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect();
+                row.resize(width, None);
+                row
+            })
+            .collect();
+
+        Ok(Pattern { cells })
+    }
+}
+
+impl Pattern {
+    // A word written left to right and along the main diagonal, the other
+    // directions are rotations and reflections of those two.
+    fn word(word: &str) -> Vec<Pattern> {
+        let letters: Vec<char> = word.chars().collect();
+        let mut diagonal = vec![vec![None; letters.len()]; letters.len()];
+        for (i, l) in letters.iter().enumerate() {
+            diagonal[i][i] = Some(*l);
+        }
+
+        vec![
+            Pattern {
+                cells: vec![letters.into_iter().map(Some).collect()],
+            },
+            Pattern { cells: diagonal },
+        ]
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.cells.len(), self.cells.first().map_or(0, |r| r.len()))
+    }
 
-    let mut count = 0;
+    fn rotate(&self) -> Pattern {
+        let (height, width) = self.size();
+        Pattern {
+            cells: (0..width)
+                .map(|i| (0..height).map(|j| self.cells[height - 1 - j][i]).collect())
+                .collect(),
+        }
+    }
 
-    for i in 0..word_search.len() {
-        for j in 0..word_search[0].len() {
-            if word_search[i][j] == 'X' {
-                count += search(&word_search, (i as isize, j as isize));
+    fn reflect(&self) -> Pattern {
+        Pattern {
+            cells: self
+                .cells
+                .iter()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    fn variants(&self) -> Vec<Pattern> {
+        let mut variants: Vec<Pattern> = vec![];
+        let mut rotated = self.clone();
+        for _ in 0..4 {
+            for variant in [rotated.reflect(), rotated.clone()] {
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
             }
+            rotated = rotated.rotate();
         }
+        variants
     }
 
-    count
+    fn matches_at(&self, grid: &[Vec<char>], pos: (usize, usize)) -> Option<Match> {
+        let mut cells = vec![];
+        for (i, row) in self.cells.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if let Some(c) = cell {
+                    let letter = grid.get(pos.0 + i).and_then(|row| row.get(pos.1 + j));
+                    if letter != Some(c) {
+                        return None;
+                    }
+                    cells.push((pos.0 + i, pos.1 + j));
+                }
+            }
+        }
+        Some(cells)
+    }
 }
 
-fn search(word_search: &[Vec<char>], pos: (isize, isize)) -> usize {
-    DIRECTIONS
-        .iter()
-        .filter(|dir| search_dir(word_search, pos, **dir))
-        .count()
+// Every placement of the patterns in all their rotations and reflections. A
+// placement shared by several of them is only reported once.
+fn find_matches(grid: &[Vec<char>], patterns: &[Pattern]) -> Vec<Match> {
+    let mut variants: Vec<Pattern> = vec![];
+    for variant in patterns.iter().flat_map(|p| p.variants()) {
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+
+    // Rows may have different lengths, cells past the end of one never match
+    let grid_width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut matches = vec![];
+    let mut seen: HashSet<Match> = HashSet::new();
+    for variant in &variants {
+        let (height, width) = variant.size();
+        for i in 0..(grid.len() + 1).saturating_sub(height) {
+            for j in 0..(grid_width + 1).saturating_sub(width) {
+                if let Some(m) = variant.matches_at(grid, (i, j)) {
+                    if seen.insert(m.clone()) {
+                        matches.push(m);
+                    }
+                }
+            }
+        }
+    }
+
+    matches
 }
 
-fn search_dir(word_search: &[Vec<char>], pos: (isize, isize), dir: (isize, isize)) -> bool {
-    for (i, l) in LETTERS.iter().enumerate() {
-        let new_pos = (
-            pos.0 + (i as isize + 1) * dir.0,
-            pos.1 + (i as isize + 1) * dir.1,
-        );
+fn highlight(grid: &[Vec<char>], matches: &[Match]) -> String {
+    let mut highlighted: Vec<Vec<char>> = grid.iter().map(|row| vec!['.'; row.len()]).collect();
+    for pos in matches.iter().flatten() {
+        highlighted[pos.0][pos.1] = grid[pos.0][pos.1];
+    }
 
-        if new_pos.0 < 0
-            || new_pos.1 < 0
-            || new_pos.0 >= word_search.len() as isize
-            || new_pos.1 >= word_search[0].len() as isize
-            || word_search[new_pos.0 as usize][new_pos.1 as usize] != *l
-        {
-            return false;
+    highlighted
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let grid = parse_grid(INPUT);
+    match args.as_slice() {
+        [] => println!("Answer: {}", process(INPUT)),
+        [command] if command == "highlight" => {
+            let matches = find_matches(&grid, &patterns());
+            println!("{}", highlight(&grid, &matches));
+            println!("Matches: {}", matches.len());
+        }
+        words => {
+            let patterns: Vec<Pattern> = words.iter().flat_map(|w| Pattern::word(w)).collect();
+            println!("Answer: {}", find_matches(&grid, &patterns).len());
         }
     }
+}
+
+fn process(input: &str) -> usize {
+    find_matches(&parse_grid(input), &patterns()).len()
+}
+
+fn parse_grid(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|l| l.chars().collect()).collect()
+}
 
-    true
+fn patterns() -> Vec<Pattern> {
+    Pattern::word("XMAS")
 }
 
 #[cfg(test)]
@@ -80,4 +193,63 @@ MAMMMXMMMM
 MXMXAXMASX"#;
         assert_eq!(process(input), 18)
     }
+
+    #[test]
+    fn highlight_matches() {
+        let grid = parse_grid(
+            r#"..X...
+.SAMX.
+.A..A.
+XMAS.S
+.X...."#,
+        );
+        let matches = find_matches(&grid, &patterns());
+
+        assert_eq!(matches.len(), 4);
+        assert_eq!(
+            highlight(&grid, &matches),
+            r#"..X...
+.SAMX.
+.A..A.
+XMAS.S
+.X...."#
+        );
+    }
+
+    #[test]
+    fn plus_shape() {
+        let grid = parse_grid(
+            r#".A.
+ABA
+.A.
+..."#,
+        );
+        let plus: Pattern = ".A.\nABA\n.A.".parse().unwrap();
+
+        assert_eq!(plus.variants().len(), 1);
+        assert_eq!(
+            find_matches(&grid, &[plus]),
+            vec![vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]]
+        );
+    }
+
+    #[test]
+    fn shared_placements() {
+        // The padded pattern is a different variant with the same letters
+        let grid = parse_grid("XMAS\n....");
+        let padded: Pattern = "XMAS\n....".parse().unwrap();
+        let mut patterns = patterns();
+        patterns.push(padded);
+
+        assert_eq!(
+            find_matches(&grid, &patterns),
+            vec![vec![(0, 0), (0, 1), (0, 2), (0, 3)]]
+        );
+    }
+
+    #[test]
+    fn ragged_grid() {
+        let grid = parse_grid("XMAS\nM\nAMAS\nS..SAMX");
+        assert_eq!(find_matches(&grid, &patterns()).len(), 3);
+    }
 }
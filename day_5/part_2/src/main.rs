@@ -1,12 +1,73 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
 
 const INPUT: &str = include_str!("../input.txt");
 
+#[derive(Debug, PartialEq)]
+enum OrderError {
+    // Pages in rule order, each one must come before the next and the last one
+    // before the first.
+    Cycle(Vec<usize>),
+    // Page listed more than once in the update, which no order can satisfy
+    // against itself.
+    Duplicate(usize),
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Cycle(pages) => {
+                let pages: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+                write!(f, "cycle {} -> {}", pages.join(" -> "), pages[0])
+            }
+            OrderError::Duplicate(page) => write!(f, "page {} appears twice", page),
+        }
+    }
+}
+
 fn main() {
-    println!("Answer: {}", process(INPUT));
+    match std::env::args().nth(1).as_deref() {
+        Some("diagnose") => diagnose(INPUT),
+        _ => println!("Answer: {}", process(INPUT)),
+    }
 }
 
 fn process(input: &str) -> usize {
+    let (order_rules, updates) = parse(input);
+
+    updates
+        .iter()
+        .filter(|u| !violations(u, &order_rules).is_empty())
+        // Updates whose rules loop have no correct order, `diagnose` reports them
+        .filter_map(|u| reorder(u, &order_rules).ok())
+        .fold(0, |acc, u| acc + u[u.len() / 2])
+}
+
+fn diagnose(input: &str) {
+    let (order_rules, updates) = parse(input);
+
+    for update in updates {
+        let pages: Vec<String> = update.iter().map(|p| p.to_string()).collect();
+        let violations = violations(&update, &order_rules);
+        if violations.is_empty() {
+            println!("{}: valid", pages.join(","));
+            continue;
+        }
+
+        let rules: Vec<String> = violations
+            .iter()
+            .map(|(before, after)| format!("{}|{}", before, after))
+            .collect();
+        match reorder(&update, &order_rules) {
+            Ok(_) => println!("{}: violates {}", pages.join(","), rules.join(" ")),
+            Err(err) => println!("{}: {}", pages.join(","), err),
+        }
+    }
+}
+
+fn parse(input: &str) -> (HashMap<usize, Vec<usize>>, Vec<Vec<usize>>) {
     let lines = input.lines().collect::<Vec<_>>();
     let split_pos = lines.iter().position(|l| l.is_empty()).unwrap();
 
@@ -21,45 +82,119 @@ fn process(input: &str) -> usize {
         order_rules.entry(pages[0]).or_default().push(pages[1]);
     }
 
-    lines[split_pos + 1..]
+    let updates = lines[split_pos + 1..]
         .iter()
         .map(|l| {
             l.split(',')
                 .map(|n| n.parse::<usize>().unwrap())
                 .collect::<Vec<usize>>()
         })
-        .filter(|u| !validate(u, &order_rules))
-        .map(|mut u| {
-            reorder(&mut u, &order_rules);
-            u
-        })
-        .fold(0, |acc, u| acc + u[u.len() / 2])
+        .collect();
+
+    (order_rules, updates)
 }
 
-fn validate(update: &[usize], order_rules: &HashMap<usize, Vec<usize>>) -> bool {
-    for i in 1..update.len() {
-        for j in 0..i {
-            if let Some(rules) = order_rules.get(&update[i]) {
-                if rules.contains(&update[j]) {
-                    return false;
-                }
+// Rules `before|after` broken by the update, sorted.
+fn violations(update: &[usize], order_rules: &HashMap<usize, Vec<usize>>) -> Vec<(usize, usize)> {
+    let positions: HashMap<usize, usize> =
+        update.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+    let mut violations = vec![];
+    for (i, page) in update.iter().enumerate() {
+        for after in order_rules.get(page).into_iter().flatten() {
+            if positions.get(after).is_some_and(|j| *j < i) {
+                violations.push((*page, *after));
             }
         }
     }
+    violations.sort();
 
-    true
+    violations
 }
 
-fn reorder(update: &mut Vec<usize>, order_rules: &HashMap<usize, Vec<usize>>) {
-    for i in 1..update.len() {
-        for j in 0..i {
-            if let Some(rules) = order_rules.get(&update[i]) {
-                if rules.contains(&update[j]) {
-                    update.swap(i, j);
-                }
+// Topological sort of the rules restricted to the pages of the update, pages
+// without rules between them can come in any order.
+fn reorder(
+    update: &[usize],
+    order_rules: &HashMap<usize, Vec<usize>>,
+) -> Result<Vec<usize>, OrderError> {
+    let mut pages: HashSet<usize> = HashSet::new();
+    if let Some(page) = update.iter().find(|p| !pages.insert(**p)) {
+        return Err(OrderError::Duplicate(*page));
+    }
+    let successors = |page: &usize| {
+        order_rules
+            .get(page)
+            .into_iter()
+            .flatten()
+            .filter(|p| pages.contains(p))
+    };
+
+    let mut in_degrees: HashMap<usize, usize> = update.iter().map(|p| (*p, 0)).collect();
+    for page in update {
+        for after in successors(page) {
+            *in_degrees.get_mut(after).unwrap() += 1;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = update
+        .iter()
+        .filter(|p| in_degrees[p] == 0)
+        .copied()
+        .collect();
+    let mut ordered = vec![];
+    while let Some(page) = queue.pop_front() {
+        ordered.push(page);
+        for after in successors(&page) {
+            let in_degree = in_degrees.get_mut(after).unwrap();
+            *in_degree -= 1;
+            if *in_degree == 0 {
+                queue.push_back(*after);
+            }
+        }
+    }
+
+    if ordered.len() == update.len() {
+        Ok(ordered)
+    } else {
+        Err(OrderError::Cycle(find_cycle(
+            update,
+            order_rules,
+            &in_degrees,
+        )))
+    }
+}
+
+// Every page left with an incoming rule after the sort has a predecessor that was
+// also left, so walking back through them has to loop.
+fn find_cycle(
+    update: &[usize],
+    order_rules: &HashMap<usize, Vec<usize>>,
+    in_degrees: &HashMap<usize, usize>,
+) -> Vec<usize> {
+    let left = |page: &usize| in_degrees.get(page).is_some_and(|d| *d > 0);
+
+    let mut predecessors: HashMap<usize, usize> = HashMap::new();
+    for page in update.iter().filter(|p| left(p)) {
+        for after in order_rules.get(page).into_iter().flatten() {
+            if left(after) {
+                predecessors.insert(*after, *page);
             }
         }
     }
+
+    let mut walk = vec![*update.iter().find(|p| left(p)).unwrap()];
+    loop {
+        let previous = predecessors[walk.last().unwrap()];
+        if let Some(start) = walk.iter().position(|p| *p == previous) {
+            let mut cycle = walk.split_off(start);
+            cycle.reverse();
+            let first = cycle.iter().position(|p| p == cycle.iter().min().unwrap());
+            cycle.rotate_left(first.unwrap());
+            return cycle;
+        }
+        walk.push(previous);
+    }
 }
 
 #[cfg(test)]
@@ -98,4 +233,49 @@ mod tests {
 97,13,75,29,47"#;
         assert_eq!(process(input), 123)
     }
+
+    #[test]
+    fn reordering() {
+        let input = r#"1|2
+2|3
+1|3
+
+2,1,3
+3,2,1"#;
+        let (order_rules, updates) = parse(input);
+        assert_eq!(violations(&updates[0], &order_rules), vec![(1, 2)]);
+        assert_eq!(
+            violations(&updates[1], &order_rules),
+            vec![(1, 2), (1, 3), (2, 3)]
+        );
+        assert_eq!(reorder(&updates[1], &order_rules), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn cycle() {
+        let input = r#"3|1
+1|2
+2|3
+4|1
+
+4,1,2,3"#;
+        let (order_rules, updates) = parse(input);
+        let err = reorder(&updates[0], &order_rules).unwrap_err();
+
+        assert_eq!(err, OrderError::Cycle(vec![1, 2, 3]));
+        assert_eq!(err.to_string(), "cycle 1 -> 2 -> 3 -> 1");
+
+        let input = format!("{}\n5,4", input.replace("4|1", "4|1\n4|5"));
+        assert_eq!(process(&input), 5);
+    }
+
+    #[test]
+    fn duplicate_pages() {
+        let (order_rules, updates) = parse("1|2\n\n2,2,1");
+        let err = reorder(&updates[0], &order_rules).unwrap_err();
+
+        assert_eq!(err, OrderError::Duplicate(2));
+        assert_eq!(err.to_string(), "page 2 appears twice");
+        assert_eq!(process("1|2\n\n2,2,1\n2,1"), 2);
+    }
 }
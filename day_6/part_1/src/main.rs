@@ -1,5 +1,6 @@
 const INPUT: &str = include_str!("../input.txt");
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Direction {
     Up,
     Down,
@@ -25,8 +26,13 @@ impl Direction {
             Direction::Left => (0, -1),
         }
     }
+
+    fn is_vertical(&self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
 }
 
+#[derive(Clone, Copy, Debug)]
 struct Guard {
     pos: (usize, usize),
     dir: Direction,
@@ -47,8 +53,8 @@ impl Guard {
     }
 
     /// Executes next move. Returns `true` when it goes out of map.
-    fn do_move(&mut self, map: &[Vec<char>], visited: &mut [Vec<bool>]) -> bool {
-        visited[self.pos.0][self.pos.1] = true;
+    fn do_move(&mut self, map: &[Vec<char>], patrol: &mut Patrol) -> bool {
+        patrol.pass(self);
 
         let new_pos = (
             self.pos.0 as isize + self.dir.get_move().0,
@@ -59,6 +65,7 @@ impl Guard {
             || new_pos.0 >= map.len() as isize
             || new_pos.1 >= map[0].len() as isize
         {
+            patrol.exit = Some(*self);
             return true;
         }
 
@@ -66,37 +73,104 @@ impl Guard {
 
         if map[new_pos.0][new_pos.1] == '#' {
             self.dir = self.dir.get_next();
+            patrol.turns.push(self.pos);
         } else {
             self.pos = new_pos;
+            patrol.steps += 1;
         }
 
         false
     }
 }
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
+struct Patrol {
+    headings: Vec<Vec<Vec<Direction>>>,
+    turns: Vec<(usize, usize)>,
+    steps: usize,
+    exit: Option<Guard>,
 }
 
-fn process(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect::<Vec<Vec<char>>>();
-    let mut visited = vec![vec![false; map[0].len()]; map.len()];
-    let mut guard = Guard::find(&map).unwrap();
-
-    visited[guard.pos.0][guard.pos.1] = true;
-
-    loop {
-        if guard.do_move(&map, &mut visited) {
-            break;
+impl Patrol {
+    fn new(map: &[Vec<char>]) -> Self {
+        Patrol {
+            headings: vec![vec![vec![]; map[0].len()]; map.len()],
+            turns: vec![],
+            steps: 0,
+            exit: None,
+        }
+    }
+
+    fn run(map: &[Vec<char>]) -> Self {
+        let mut patrol = Patrol::new(map);
+        let mut guard = Guard::find(map).unwrap();
+
+        while !guard.do_move(map, &mut patrol) {}
+
+        patrol
+    }
+
+    fn pass(&mut self, guard: &Guard) {
+        let headings = &mut self.headings[guard.pos.0][guard.pos.1];
+        if !headings.contains(&guard.dir) {
+            headings.push(guard.dir);
+        }
+    }
+
+    fn visited(&self) -> usize {
+        self.headings
+            .iter()
+            .map(|row| row.iter().filter(|h| !h.is_empty()).count())
+            .sum()
+    }
+
+    // Path drawn over the map the way the puzzle illustrates it: `|` and `-` for
+    // vertical and horizontal moves, `+` where the guard went both ways.
+    fn overlay(&self, map: &[Vec<char>]) -> String {
+        map.iter()
+            .zip(&self.headings)
+            .map(|(row, headings)| {
+                row.iter()
+                    .zip(headings)
+                    .map(|(c, h)| {
+                        let vertical = h.iter().any(|d| d.is_vertical());
+                        let horizontal = h.iter().any(|d| !d.is_vertical());
+                        match (*c, vertical, horizontal) {
+                            ('.', true, true) => '+',
+                            ('.', true, false) => '|',
+                            ('.', false, true) => '-',
+                            (c, _, _) => c,
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("report") => {
+            let map = parse_map(INPUT);
+            let patrol = Patrol::run(&map);
+
+            println!("{}", patrol.overlay(&map));
+            println!("Turns: {:?}", patrol.turns);
+            println!("Steps: {}", patrol.steps);
+            if let Some(exit) = patrol.exit {
+                println!("Exit: {:?} heading {:?}", exit.pos, exit.dir);
+            }
         }
+        _ => println!("Answer: {}", process(INPUT)),
     }
+}
 
-    visited
-        .iter()
-        .fold(0, |acc, row| acc + row.iter().filter(|x| **x).count())
+fn process(input: &str) -> usize {
+    Patrol::run(&parse_map(input)).visited()
+}
+
+fn parse_map(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|l| l.chars().collect()).collect()
 }
 
 #[cfg(test)]
@@ -117,4 +191,28 @@ mod tests {
 ......#..."#;
         assert_eq!(process(input), 41)
     }
+
+    #[test]
+    fn report() {
+        let input = r#".#..
+...#
+.^..
+..#."#;
+        let map = parse_map(input);
+        let patrol = Patrol::run(&map);
+
+        assert_eq!(
+            patrol.overlay(&map),
+            r#".#..
+.++#
+-^+.
+..#."#
+        );
+        assert_eq!(patrol.turns, [(1, 1), (1, 2), (2, 2)]);
+        assert_eq!(patrol.steps, 5);
+        assert_eq!(patrol.headings[2][1], vec![Direction::Up, Direction::Left]);
+
+        let exit = patrol.exit.unwrap();
+        assert_eq!((exit.pos, exit.dir), ((2, 0), Direction::Left));
+    }
 }
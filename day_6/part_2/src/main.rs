@@ -1,22 +1,29 @@
 const INPUT: &str = include_str!("../input.txt");
 
-#[derive(Clone, Copy, PartialEq)]
+type Pos = (usize, usize);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 enum Direction {
     Up,
     Down,
     Right,
     Left,
-    None,
 }
 
 impl Direction {
+    const VALUES: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Right,
+        Direction::Left,
+    ];
+
     fn get_next(&self) -> Direction {
         match self {
             Direction::Up => Direction::Right,
             Direction::Right => Direction::Down,
             Direction::Down => Direction::Left,
             Direction::Left => Direction::Up,
-            Direction::None => Direction::None,
         }
     }
 
@@ -26,18 +33,15 @@ impl Direction {
             Direction::Right => (0, 1),
             Direction::Down => (1, 0),
             Direction::Left => (0, -1),
-            Direction::None => (0, 0),
         }
     }
-}
 
-enum MoveResult {
-    Loop,
-    OutOfBounds,
-    InProgress,
+    fn index(&self) -> usize {
+        *self as usize
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 struct Guard {
     pos: (usize, usize),
     dir: Direction,
@@ -56,82 +60,171 @@ impl Guard {
 
         None
     }
+}
 
-    fn do_move(
-        &mut self,
-        map: &[Vec<char>],
-        visited: &mut [Vec<Direction>],
-        new_obstacle_pos: (usize, usize),
-    ) -> MoveResult {
-        let new_pos = (
-            self.pos.0 as isize + self.dir.get_move().0,
-            self.pos.1 as isize + self.dir.get_move().1,
-        );
-        if new_pos.0 < 0
-            || new_pos.1 < 0
-            || new_pos.0 >= map.len() as isize
-            || new_pos.1 >= map[0].len() as isize
-        {
-            return MoveResult::OutOfBounds;
-        }
+struct Lab {
+    map: Vec<Vec<char>>,
+    // For every cell and heading, where the guard stops in front of the next
+    // obstacle, or `None` when the guard walks out of the map instead.
+    stops: Vec<Vec<[Option<Pos>; 4]>>,
+}
+
+impl Lab {
+    fn new(map: Vec<Vec<char>>) -> Self {
+        let mut stops = vec![vec![[None; 4]; map[0].len()]; map.len()];
 
-        let new_pos = (new_pos.0 as usize, new_pos.1 as usize);
+        for dir in Direction::VALUES {
+            let (di, dj) = dir.get_move();
+            let rows: Vec<usize> = if di > 0 {
+                (0..map.len()).rev().collect()
+            } else {
+                (0..map.len()).collect()
+            };
+            let cols: Vec<usize> = if dj > 0 {
+                (0..map[0].len()).rev().collect()
+            } else {
+                (0..map[0].len()).collect()
+            };
 
-        if visited[new_pos.0][new_pos.1] == self.dir {
-            return MoveResult::Loop;
+            for i in &rows {
+                for j in &cols {
+                    stops[*i][*j][dir.index()] = match next_pos(&map, (*i, *j), dir) {
+                        None => None,
+                        Some(next) if map[next.0][next.1] == '#' => Some((*i, *j)),
+                        Some(next) => stops[next.0][next.1][dir.index()],
+                    };
+                }
+            }
         }
 
-        if map[new_pos.0][new_pos.1] == '#' || new_pos == new_obstacle_pos {
-            self.dir = self.dir.get_next();
-        } else {
-            self.pos = new_pos;
-            visited[self.pos.0][self.pos.1] = self.dir;
+        Lab { map, stops }
+    }
+
+    // Original path of the guard, as each newly visited cell along with the state
+    // the guard is in right before stepping onto it. Ends when the guard leaves
+    // the map or takes a turn it already took.
+    fn path(&self, mut guard: Guard) -> Vec<((usize, usize), Guard)> {
+        let mut seen = vec![vec![false; self.map[0].len()]; self.map.len()];
+        seen[guard.pos.0][guard.pos.1] = true;
+        let mut turns = vec![false; self.map.len() * self.map[0].len() * 4];
+
+        let mut path = vec![];
+        while let Some(next) = next_pos(&self.map, guard.pos, guard.dir) {
+            if self.map[next.0][next.1] == '#' {
+                guard.dir = guard.dir.get_next();
+
+                let state = (guard.pos.0 * self.map[0].len() + guard.pos.1) * 4 + guard.dir.index();
+                if turns[state] {
+                    break;
+                }
+                turns[state] = true;
+            } else {
+                if !seen[next.0][next.1] {
+                    seen[next.0][next.1] = true;
+                    path.push((next, guard));
+                }
+                guard.pos = next;
+            }
         }
 
-        MoveResult::InProgress
+        path
     }
-}
 
-fn main() {
-    println!("Answer: {}", process(INPUT));
-}
+    fn find_loop_obstacles(&self, guard: Guard) -> Vec<(usize, usize)> {
+        let mut seen = vec![0; self.map.len() * self.map[0].len() * 4];
 
-fn process(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect::<Vec<Vec<char>>>();
-    let guard = Guard::find(&map).unwrap();
+        self.path(guard)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, (obstacle, before))| {
+                self.causes_loop(*before, *obstacle, &mut seen, *i as u32 + 1)
+            })
+            .map(|(_, (obstacle, _))| obstacle)
+            .collect()
+    }
 
-    let mut count = 0;
+    // Jumps from turn to turn, `seen` holds the turns already taken for the current
+    // `stamp` so that it does not need to be cleared between obstacles.
+    fn causes_loop(
+        &self,
+        mut guard: Guard,
+        obstacle: (usize, usize),
+        seen: &mut [u32],
+        stamp: u32,
+    ) -> bool {
+        while let Some(stop) = self.stop(guard, obstacle) {
+            guard.pos = stop;
+            guard.dir = guard.dir.get_next();
 
-    for i in 0..map.len() {
-        for j in 0..map[0].len() {
-            if map[i][j] == '#' || guard.pos == (i, j) {
-                continue;
+            let state = (stop.0 * self.map[0].len() + stop.1) * 4 + guard.dir.index();
+            if seen[state] == stamp {
+                return true;
             }
+            seen[state] = stamp;
+        }
 
-            let mut visited = vec![vec![Direction::None; map[0].len()]; map.len()];
-            visited[guard.pos.0][guard.pos.1] = Direction::Up;
-
-            let mut guard = guard;
-
-            loop {
-                match guard.do_move(&map, &mut visited, (i, j)) {
-                    MoveResult::Loop => {
-                        count += 1;
-                        break;
-                    }
-                    MoveResult::OutOfBounds => {
-                        break;
-                    }
-                    MoveResult::InProgress => {}
-                }
+        false
+    }
+
+    fn stop(&self, guard: Guard, obstacle: (usize, usize)) -> Option<(usize, usize)> {
+        let stop = self.stops[guard.pos.0][guard.pos.1][guard.dir.index()];
+
+        let (di, dj) = guard.dir.get_move();
+        let to_obstacle = (
+            obstacle.0 as isize - guard.pos.0 as isize,
+            obstacle.1 as isize - guard.pos.1 as isize,
+        );
+        let steps = to_obstacle.0 * di + to_obstacle.1 * dj;
+        if steps <= 0 || (di * steps, dj * steps) != to_obstacle {
+            return stop;
+        }
+
+        let steps_to_stop = stop.map(|s| {
+            (s.0 as isize - guard.pos.0 as isize) * di + (s.1 as isize - guard.pos.1 as isize) * dj
+        });
+        if steps_to_stop.is_some_and(|to_stop| to_stop < steps) {
+            return stop;
+        }
+
+        Some((
+            (guard.pos.0 as isize + di * (steps - 1)) as usize,
+            (guard.pos.1 as isize + dj * (steps - 1)) as usize,
+        ))
+    }
+}
+
+fn next_pos(map: &[Vec<char>], pos: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+    let (di, dj) = dir.get_move();
+    let next = (pos.0.checked_add_signed(di)?, pos.1.checked_add_signed(dj)?);
+    if next.0 < map.len() && next.1 < map[0].len() {
+        Some(next)
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let map = parse_map(INPUT);
+    match std::env::args().nth(1).as_deref() {
+        Some("positions") => {
+            let guard = Guard::find(&map).unwrap();
+            for pos in Lab::new(map).find_loop_obstacles(guard) {
+                println!("{:?}", pos);
             }
         }
+        _ => println!("Answer: {}", process(INPUT)),
     }
+}
+
+fn process(input: &str) -> usize {
+    let map = parse_map(input);
+    let guard = Guard::find(&map).unwrap();
 
-    count
+    Lab::new(map).find_loop_obstacles(guard).len()
+}
+
+fn parse_map(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|l| l.chars().collect()).collect()
 }
 
 #[cfg(test)]
@@ -152,4 +245,29 @@ mod tests {
 ......#..."#;
         assert_eq!(process(input), 6)
     }
+
+    #[test]
+    fn loop_obstacles() {
+        let input = r#".#..
+...#
+.^..
+..#."#;
+        let map = parse_map(input);
+        let guard = Guard::find(&map).unwrap();
+        let obstacles = Lab::new(map).find_loop_obstacles(guard);
+
+        assert_eq!(obstacles, vec![(2, 0)]);
+    }
+
+    #[test]
+    fn looping_guard() {
+        let input = r#".#..
+...#
+#^..
+..#."#;
+        let map = parse_map(input);
+        let guard = Guard::find(&map).unwrap();
+        assert_eq!(Lab::new(map).path(guard).len(), 3);
+        assert_eq!(process(input), 0);
+    }
 }
@@ -2,51 +2,206 @@ use std::{num::ParseIntError, str::FromStr};
 
 const INPUT: &str = include_str!("../input.txt");
 
-#[derive(Clone, Copy, Debug)]
-enum Operations {
-    Add,
-    Mult,
-    Concat,
-}
-
-impl Operations {
-    const VALUES: [Self; 3] = [Self::Add, Self::Mult, Self::Concat];
-
-    fn exec(&self, a: usize, b: usize) -> usize {
-        match *self {
-            Operations::Add => a + b,
-            Operations::Mult => a * b,
-            Operations::Concat => {
-                let b_nb_digits: u32 = f64::from(b as u32).log10() as u32 + 1;
-                a * 10usize.pow(b_nb_digits) + b
-            }
+trait Operator {
+    fn symbol(&self) -> String;
+
+    // `None` when the result does not fit in a `usize` or is undefined.
+    fn apply(&self, a: usize, b: usize) -> Option<usize>;
+
+    // Inverse of `apply` on the result: the left operand that turns into `result`
+    // with `b`, if there is one. Only called when `invertible` is true.
+    fn undo(&self, _result: usize, _b: usize) -> Option<usize> {
+        None
+    }
+
+    // Whether any left operand turns into `result` with `b`, like `a * 0 = 0`.
+    fn absorbs(&self, _result: usize, _b: usize) -> bool {
+        false
+    }
+
+    fn invertible(&self) -> bool {
+        false
+    }
+}
+
+struct Add;
+struct Mult;
+struct Sub;
+struct Pow;
+struct Xor;
+struct Concat {
+    base: usize,
+}
+
+impl Operator for Add {
+    fn symbol(&self) -> String {
+        "+".to_string()
+    }
+
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        a.checked_add(b)
+    }
+
+    fn undo(&self, result: usize, b: usize) -> Option<usize> {
+        result.checked_sub(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+}
+
+impl Operator for Mult {
+    fn symbol(&self) -> String {
+        "*".to_string()
+    }
+
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        a.checked_mul(b)
+    }
+
+    fn undo(&self, result: usize, b: usize) -> Option<usize> {
+        (b != 0 && result.is_multiple_of(b)).then(|| result / b)
+    }
+
+    fn absorbs(&self, result: usize, b: usize) -> bool {
+        b == 0 && result == 0
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+}
+
+impl Operator for Sub {
+    fn symbol(&self) -> String {
+        "-".to_string()
+    }
+
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        a.checked_sub(b)
+    }
+
+    fn undo(&self, result: usize, b: usize) -> Option<usize> {
+        result.checked_add(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+}
+
+impl Operator for Pow {
+    fn symbol(&self) -> String {
+        "**".to_string()
+    }
+
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        a.checked_pow(b.try_into().ok()?)
+    }
+}
+
+impl Operator for Xor {
+    fn symbol(&self) -> String {
+        "^".to_string()
+    }
+
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        Some(a ^ b)
+    }
+
+    fn undo(&self, result: usize, b: usize) -> Option<usize> {
+        Some(result ^ b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+}
+
+impl Concat {
+    // `None` when the shift is larger than any `usize`.
+    fn shift(&self, b: usize) -> Option<usize> {
+        self.base.checked_pow(nb_digits(b, self.base))
+    }
+}
+
+impl Operator for Concat {
+    fn symbol(&self) -> String {
+        match self.base {
+            10 => "||".to_string(),
+            base => format!("||{}", base),
         }
     }
 
-    fn get_possibilites(length: usize) -> Vec<Vec<Operations>> {
-        fn helper(possibilities: Vec<Vec<Operations>>, length: usize) -> Vec<Vec<Operations>> {
-            if let Some(first) = possibilities.first() {
-                if first.len() == length {
-                    return possibilities;
-                }
-            }
+    fn apply(&self, a: usize, b: usize) -> Option<usize> {
+        a.checked_mul(self.shift(b)?)?.checked_add(b)
+    }
 
-            let mut new_possibilities = vec![];
-            for pos in possibilities.iter() {
-                for op in Operations::VALUES.iter() {
-                    let mut new_pos = pos.clone();
-                    new_pos.push(*op);
-                    new_possibilities.push(new_pos);
-                }
-            }
+    fn undo(&self, result: usize, b: usize) -> Option<usize> {
+        let shift = self.shift(b)?;
+        (result % shift == b).then(|| result / shift)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+}
 
-            helper(new_possibilities, length)
+fn nb_digits(mut n: usize, base: usize) -> u32 {
+    let mut digits = 1;
+    while n >= base {
+        n /= base;
+        digits += 1;
+    }
+    digits
+}
+
+struct Operators {
+    ops: Vec<Box<dyn Operator>>,
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        let mut operators = Operators { ops: vec![] };
+        operators
+            .register(Add)
+            .register(Mult)
+            .register(Concat { base: 10 });
+        operators
+    }
+}
+
+impl Operators {
+    fn register(&mut self, op: impl Operator + 'static) -> &mut Self {
+        self.ops.push(Box::new(op));
+        self
+    }
+
+    fn from_symbols(symbols: &[String]) -> Option<Self> {
+        let mut operators = Operators { ops: vec![] };
+        for symbol in symbols {
+            match symbol.as_str() {
+                "+" => operators.register(Add),
+                "*" => operators.register(Mult),
+                "-" => operators.register(Sub),
+                "**" => operators.register(Pow),
+                "^" => operators.register(Xor),
+                "||" => operators.register(Concat { base: 10 }),
+                _ => operators.register(Concat {
+                    base: symbol
+                        .strip_prefix("||")?
+                        .parse()
+                        .ok()
+                        .filter(|b| *b >= 2)?,
+                }),
+            };
         }
+        Some(operators)
+    }
 
-        helper(
-            Operations::VALUES.iter().map(|op| vec![*op]).collect(),
-            length,
-        )
+    fn invertible(&self) -> bool {
+        self.ops.iter().all(|op| op.invertible())
     }
 }
 
@@ -57,7 +212,8 @@ struct Line {
 
 #[derive(Debug)]
 enum ParseError {
-    ParseInt(ParseIntError),
+    // Only shown through `Debug` when a line fails to parse
+    ParseInt(#[allow(dead_code)] ParseIntError),
     WrongStructure,
 }
 
@@ -87,31 +243,153 @@ impl FromStr for Line {
 }
 
 impl Line {
-    fn validate(&self) -> bool {
-        let op_possibilites = Operations::get_possibilites(self.numbers.len() - 1);
-
-        op_possibilites.into_iter().any(|possibilites| {
-            let mut total = self.numbers[0];
-            for (n, op) in self.numbers.iter().skip(1).zip(possibilites.iter()) {
-                total = op.exec(total, *n);
-                if total > self.result {
-                    return false;
+    fn validate(&self, operators: &Operators) -> bool {
+        self.solve(operators).is_some()
+    }
+
+    // When every operator can be undone, works backwards from the result, undoing
+    // the last operation first, so that only the operations that can still lead
+    // to the first number get explored. Otherwise tries every operator forwards.
+    fn solve<'a>(&self, operators: &'a Operators) -> Option<Vec<&'a dyn Operator>> {
+        let mut ops = vec![];
+        if operators.invertible() {
+            if self.solve_until(operators, self.result, self.numbers.len() - 1, &mut ops) {
+                ops.reverse();
+                return Some(ops);
+            }
+        } else if self.solve_from(operators, self.numbers[0], 1, &mut ops) {
+            return Some(ops);
+        }
+        None
+    }
+
+    fn solve_until<'a>(
+        &self,
+        operators: &'a Operators,
+        result: usize,
+        last: usize,
+        ops: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        if last == 0 {
+            return result == self.numbers[0];
+        }
+
+        for op in &operators.ops {
+            if op.absorbs(result, self.numbers[last]) {
+                // The numbers before only have to evaluate to something
+                let mut prefix = vec![];
+                if self.solve_prefix(operators, self.numbers[0], 1, last, &mut prefix) {
+                    ops.push(op.as_ref());
+                    ops.extend(prefix.into_iter().rev());
+                    return true;
                 }
             }
-            total == self.result
-        })
+            if let Some(previous) = op.undo(result, self.numbers[last]) {
+                ops.push(op.as_ref());
+                if self.solve_until(operators, previous, last - 1, ops) {
+                    return true;
+                }
+                ops.pop();
+            }
+        }
+
+        false
+    }
+
+    fn solve_prefix<'a>(
+        &self,
+        operators: &'a Operators,
+        total: usize,
+        next: usize,
+        end: usize,
+        ops: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        if next == end {
+            return true;
+        }
+
+        for op in &operators.ops {
+            if let Some(total) = op.apply(total, self.numbers[next]) {
+                ops.push(op.as_ref());
+                if self.solve_prefix(operators, total, next + 1, end, ops) {
+                    return true;
+                }
+                ops.pop();
+            }
+        }
+
+        false
+    }
+
+    fn solve_from<'a>(
+        &self,
+        operators: &'a Operators,
+        total: usize,
+        next: usize,
+        ops: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        if next == self.numbers.len() {
+            return total == self.result;
+        }
+
+        for op in &operators.ops {
+            if let Some(total) = op.apply(total, self.numbers[next]) {
+                ops.push(op.as_ref());
+                if self.solve_from(operators, total, next + 1, ops) {
+                    return true;
+                }
+                ops.pop();
+            }
+        }
+
+        false
+    }
+
+    fn equation(&self, ops: &[&dyn Operator]) -> String {
+        let mut equation = format!("{} = {}", self.result, self.numbers[0]);
+        for (n, op) in self.numbers.iter().skip(1).zip(ops) {
+            equation.push_str(&format!(" {} {}", op.symbol(), n));
+        }
+        equation
     }
 }
 
 fn main() {
-    println!("Answer: {}", process(INPUT));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => println!("Answer: {}", process(INPUT)),
+        [command, symbols @ ..] if command == "explain" => {
+            let operators = operators_from_args(symbols);
+            for line in INPUT.lines().map(|l| l.parse::<Line>().unwrap()) {
+                if let Some(ops) = line.solve(&operators) {
+                    println!("{}", line.equation(&ops));
+                }
+            }
+        }
+        symbols => {
+            let operators = operators_from_args(symbols);
+            println!("Answer: {}", calibrate(INPUT, &operators));
+        }
+    }
+}
+
+fn operators_from_args(symbols: &[String]) -> Operators {
+    if symbols.is_empty() {
+        Operators::default()
+    } else {
+        Operators::from_symbols(symbols).expect("unknown operator")
+    }
 }
 
 fn process(input: &str) -> usize {
+    calibrate(input, &Operators::default())
+}
+
+fn calibrate(input: &str, operators: &Operators) -> usize {
     input
         .lines()
         .map(|l| l.parse::<Line>().unwrap())
-        .filter(|l| l.validate())
+        .filter(|l| l.validate(operators))
         .fold(0, |acc, n| acc + n.result)
 }
 
@@ -135,6 +413,66 @@ mod tests {
 
     #[test]
     fn op() {
-        assert_eq!(Operations::Concat.exec(12, 34), 1234)
+        assert_eq!(Concat { base: 10 }.apply(12, 34), Some(1234))
+    }
+
+    #[test]
+    fn multiplication_by_zero() {
+        assert_eq!(process("3: 9 0 3"), 3);
+        assert_eq!(process("0: 4 5 0"), 0);
+
+        let operators = Operators::default();
+        let line: Line = "3: 9 0 3".parse().unwrap();
+        let ops = line.solve(&operators).unwrap();
+        assert_eq!(line.equation(&ops), "3 = 9 * 0 + 3");
+        let line: Line = "4: 2 3 0 4".parse().unwrap();
+        let ops = line.solve(&operators).unwrap();
+        assert_eq!(line.equation(&ops), "4 = 2 + 3 * 0 + 4");
+    }
+
+    #[test]
+    fn solution() {
+        let operators = Operators::default();
+
+        let line: Line = "3267: 81 40 27".parse().unwrap();
+        let ops = line.solve(&operators).unwrap();
+        assert_eq!(line.equation(&ops), "3267 = 81 * 40 + 27");
+        let line: Line = "83: 17 5".parse().unwrap();
+        assert!(line.solve(&operators).is_none());
+
+        let line: Line = "7290: 6 8 6 15".parse().unwrap();
+        let ops = line.solve(&operators).unwrap();
+        assert_eq!(line.equation(&ops), "7290 = 6 * 8 || 6 * 15");
+    }
+
+    #[test]
+    fn custom_operators() {
+        assert_eq!(Concat { base: 10 }.apply(1, 4294967296), Some(14294967296));
+        assert_eq!(Concat { base: 10 }.apply(1, usize::MAX), None);
+        assert_eq!(Concat { base: 10 }.undo(usize::MAX, usize::MAX), None);
+        let concat = Operators::from_symbols(&["||".to_string()]).unwrap();
+        let line: Line = format!("{}: 0 {}", usize::MAX, usize::MAX).parse().unwrap();
+        assert!(line.solve(&concat).is_none());
+        assert_eq!(nb_digits(usize::MAX, 10), 20);
+
+        let symbols = ["+", "-", "**", "^", "||2"].map(String::from);
+        let operators = Operators::from_symbols(&symbols).unwrap();
+        assert!(!operators.invertible());
+        for (input, equation) in [
+            ("5: 10 5", "5 = 10 - 5"),
+            ("1000: 10 3", "1000 = 10 ** 3"),
+            ("6: 3 5", "6 = 3 ^ 5"),
+            ("7: 3 1", "7 = 3 ||2 1"),
+        ] {
+            let line: Line = input.parse().unwrap();
+            let ops = line.solve(&operators).unwrap();
+            assert_eq!(line.equation(&ops), equation);
+        }
+
+        let operators = Operators::from_symbols(&["-", "^"].map(String::from)).unwrap();
+        assert!(operators.invertible());
+        let line: Line = "6: 10 5 3".parse().unwrap();
+        let ops = line.solve(&operators).unwrap();
+        assert_eq!(line.equation(&ops), "6 = 10 - 5 ^ 3");
     }
 }
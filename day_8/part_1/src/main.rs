@@ -1,34 +1,122 @@
-use std::collections::{HashMap, HashSet};
-use std::mem::swap;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../input.txt");
 
+type Pos = (isize, isize);
+type PairAntinodes = HashMap<(Pos, Pos), Vec<Pos>>;
+
+// Position of an antinode along an antenna pair, as a fraction of the distance
+// between the two antennas, measured from either one of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Ratio {
+    num: isize,
+    den: isize,
+}
+
+impl FromStr for Ratio {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num, den) = s.split_once('/').unwrap_or((s, "1"));
+        let num = num.trim().parse().map_err(|_| ())?;
+        let den = den.trim().parse().map_err(|_| ())?;
+        if den == 0 {
+            return Err(());
+        }
+        Ok(Ratio { num, den })
+    }
+}
+
+impl Ratio {
+    fn apply(&self, from: Pos, delta: Pos) -> Option<Pos> {
+        let offset = (delta.0 * self.num, delta.1 * self.num);
+        if offset.0 % self.den != 0 || offset.1 % self.den != 0 {
+            return None;
+        }
+        Some((from.0 + offset.0 / self.den, from.1 + offset.1 / self.den))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Harmonics {
+    Ratios(Vec<Ratio>),
+    Line,
+}
+
+impl Default for Harmonics {
+    fn default() -> Self {
+        Harmonics::Ratios(vec![Ratio { num: 2, den: 1 }])
+    }
+}
+
+impl FromStr for Harmonics {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(Harmonics::Line),
+            _ => Ok(Harmonics::Ratios(
+                s.split(',').map(|r| r.parse()).collect::<Result<_, _>>()?,
+            )),
+        }
+    }
+}
+
 fn main() {
-    println!("Answer: {}", process(INPUT));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => println!("Answer: {}", process(INPUT)),
+        [command, filter @ ..] if command == "render" => {
+            let map = parse_map(INPUT);
+            let antinodes = find_antinodes(
+                (map.len(), map[0].len()),
+                &find_antennas(&map),
+                &Harmonics::default(),
+            );
+            let filter = filter.first().and_then(|f| f.chars().next());
+            println!("{}", render(&map, &antinodes, filter));
+            println!("{}", legend(&antinodes));
+        }
+        [harmonics, ..] => {
+            let harmonics = harmonics.parse().expect("invalid harmonics");
+            let map = parse_map(INPUT);
+            let antinodes =
+                find_antinodes((map.len(), map[0].len()), &find_antennas(&map), &harmonics);
+            println!("Answer: {}", count_antinodes(&antinodes));
+        }
+    }
 }
 
 fn process(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect::<Vec<Vec<char>>>();
+    let map = parse_map(input);
 
     let freq_antennas = find_antennas(&map);
+    let antinodes = find_antinodes(
+        (map.len(), map[0].len()),
+        &freq_antennas,
+        &Harmonics::default(),
+    );
+
+    count_antinodes(&antinodes)
+}
 
-    get_antinode_count((map.len(), map[0].len()), freq_antennas)
+fn parse_map(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|l| l.chars().collect()).collect()
 }
 
-fn find_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<(isize, isize)>> {
-    let mut freq_antennas: HashMap<char, Vec<(isize, isize)>> = HashMap::new();
+fn find_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<Pos>> {
+    let mut freq_antennas: HashMap<char, Vec<Pos>> = HashMap::new();
 
-    for i in 0..map.len() {
-        for j in 0..map[0].len() {
-            let freq = map[i][j];
-            if freq != '.' {
+    for (i, row) in map.iter().enumerate() {
+        for (j, freq) in row.iter().enumerate() {
+            if *freq != '.' {
                 freq_antennas
-                    .entry(freq)
+                    .entry(*freq)
                     .or_default()
                     .push((i as isize, j as isize));
             }
@@ -37,49 +125,140 @@ fn find_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<(isize, isize)>> {
     freq_antennas
 }
 
-fn get_antinode_count(
+fn find_antinodes(
     map_size: (usize, usize),
-    freq_antennas: HashMap<char, Vec<(isize, isize)>>,
-) -> usize {
-    let mut antinodes: HashSet<(isize, isize)> = HashSet::new();
+    freq_antennas: &HashMap<char, Vec<Pos>>,
+    harmonics: &Harmonics,
+) -> HashMap<char, PairAntinodes> {
+    freq_antennas
+        .iter()
+        .map(|(freq, antennas)| {
+            let pairs = antennas
+                .iter()
+                .tuple_combinations::<(_, _)>()
+                .map(|(a, b)| ((*a, *b), calculate_antinodes(map_size, *a, *b, harmonics)))
+                .collect();
+            (*freq, pairs)
+        })
+        .collect()
+}
+
+fn count_antinodes(antinodes: &HashMap<char, PairAntinodes>) -> usize {
+    antinodes
+        .values()
+        .flat_map(|pairs| pairs.values().flatten())
+        .collect::<HashSet<_>>()
+        .len()
+}
 
-    freq_antennas.values().for_each(|v| {
-        v.iter()
-            .tuple_combinations::<(_, _)>()
-            .flat_map(|(a, b)| calculate_antinodes(map_size, *a, *b))
-            .for_each(|pos| {
-                antinodes.insert(pos);
-            })
-    });
+fn render(
+    map: &[Vec<char>],
+    antinodes: &HashMap<char, PairAntinodes>,
+    filter: Option<char>,
+) -> String {
+    let shown = |freq: &char| filter.is_none_or(|f| f == *freq);
 
-    antinodes.len()
+    let mut grid: Vec<Vec<char>> = map
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|c| if *c != '.' && shown(c) { *c } else { '.' })
+                .collect()
+        })
+        .collect();
+
+    for (_, pairs) in antinodes.iter().filter(|(freq, _)| shown(freq)) {
+        for pos in pairs.values().flatten() {
+            let cell = &mut grid[pos.0 as usize][pos.1 as usize];
+            if *cell == '.' {
+                *cell = '#';
+            }
+        }
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .join("\n")
+}
+
+fn legend(antinodes: &HashMap<char, PairAntinodes>) -> String {
+    let by_freq: HashMap<char, HashSet<Pos>> = antinodes
+        .iter()
+        .map(|(freq, pairs)| (*freq, pairs.values().flatten().copied().collect()))
+        .collect();
+
+    by_freq
+        .iter()
+        .sorted_by_key(|(freq, _)| **freq)
+        .map(|(freq, positions)| {
+            let shared = positions
+                .iter()
+                .filter(|pos| {
+                    by_freq
+                        .iter()
+                        .any(|(other, others)| other != freq && others.contains(pos))
+                })
+                .count();
+            format!(
+                "{}: {} antinodes ({} shared)",
+                freq,
+                positions.len(),
+                shared
+            )
+        })
+        .join("\n")
 }
 
 fn calculate_antinodes(
     map_size: (usize, usize),
-    mut a: (isize, isize),
-    mut b: (isize, isize),
-) -> Vec<(isize, isize)> {
+    a: Pos,
+    b: Pos,
+    harmonics: &Harmonics,
+) -> Vec<Pos> {
     let mut antinodes = vec![];
+    let delta = (b.0 - a.0, b.1 - a.1);
+
+    match harmonics {
+        Harmonics::Ratios(ratios) => {
+            for r in ratios {
+                let candidates = [r.apply(a, delta), r.apply(b, (-delta.0, -delta.1))];
+                for pos in candidates.into_iter().flatten() {
+                    if valid_pos(map_size, &pos) && !antinodes.contains(&pos) {
+                        antinodes.push(pos);
+                    }
+                }
+            }
+        }
+        Harmonics::Line => {
+            let divisor = gcd(delta.0.abs(), delta.1.abs()).max(1);
+            let step = (delta.0 / divisor, delta.1 / divisor);
 
-    let distance = (a.0 - b.0, a.1 - b.1);
+            let mut backward = a;
+            while valid_pos(map_size, &backward) {
+                antinodes.push(backward);
+                backward = (backward.0 - step.0, backward.1 - step.1);
+            }
 
-    if (a.0 - distance.0, a.1 - distance.1) == b {
-        swap(&mut a, &mut b);
-    }
-    let first = (a.0 - distance.0, a.1 - distance.1);
-    if valid_pos(map_size, &first) {
-        antinodes.push(first);
-    }
-    let second = (b.0 + distance.0, b.1 + distance.1);
-    if valid_pos(map_size, &second) {
-        antinodes.push(second);
+            let mut forward = (a.0 + step.0, a.1 + step.1);
+            while valid_pos(map_size, &forward) {
+                antinodes.push(forward);
+                forward = (forward.0 + step.0, forward.1 + step.1);
+            }
+        }
     }
 
     antinodes
 }
 
-fn valid_pos(map_size: (usize, usize), pos: &(isize, isize)) -> bool {
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn valid_pos(map_size: (usize, usize), pos: &Pos) -> bool {
     pos.0 >= 0 && pos.1 >= 0 && pos.0 < map_size.0 as isize && pos.1 < map_size.1 as isize
 }
 
@@ -148,4 +327,83 @@ mod tests {
 ....0..0.."#;
         assert_eq!(process(input), 5)
     }
+
+    #[test]
+    fn rational_harmonics() {
+        let map = parse_map(
+            r#"a.........
+..........
+..........
+...a......
+..........
+..........
+..........
+..........
+..........
+.........."#,
+        );
+        let freq_antennas = find_antennas(&map);
+        let harmonics: Harmonics = "1/3,2".parse().unwrap();
+        let antinodes = find_antinodes((10, 10), &freq_antennas, &harmonics);
+
+        assert_eq!(
+            antinodes[&'a'][&((0, 0), (3, 3))],
+            vec![(1, 1), (2, 2), (6, 6)]
+        );
+        assert_eq!(count_antinodes(&antinodes), 3);
+    }
+
+    #[test]
+    fn render_example() {
+        let map = parse_map(
+            r#"............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............"#,
+        );
+        let antinodes = find_antinodes((12, 12), &find_antennas(&map), &Harmonics::default());
+
+        assert_eq!(
+            render(&map, &antinodes, None),
+            r#"......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#."#
+        );
+        assert_eq!(
+            render(&map, &antinodes, Some('A')),
+            r#"............
+...#........
+....#.......
+............
+............
+......A.....
+............
+.......#....
+........A...
+.........A..
+..........#.
+..........#."#
+        );
+        assert_eq!(
+            legend(&antinodes),
+            "0: 10 antinodes (1 shared)\nA: 5 antinodes (1 shared)"
+        );
+    }
 }
@@ -1,4 +1,9 @@
-// horrible solution
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    fmt::Display,
+    str::FromStr,
+};
 
 const INPUT: &str = include_str!("../input.txt");
 
@@ -22,24 +27,76 @@ impl DiskUnit {
             DiskUnit::Empty { size } => *size,
         }
     }
+}
 
-    fn empty(&mut self) {
-        match self {
-            DiskUnit::File { id: _, size } => *self = DiskUnit::Empty { size: *size },
-            DiskUnit::Empty { size: _ } => {}
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Strategy {
+    BlockByBlock,
+    FirstFit,
+    BestFit,
+    WorstFit,
+    NextFit,
+}
+
+impl FromStr for Strategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block-by-block" => Ok(Strategy::BlockByBlock),
+            "first-fit" => Ok(Strategy::FirstFit),
+            "best-fit" => Ok(Strategy::BestFit),
+            "worst-fit" => Ok(Strategy::WorstFit),
+            "next-fit" => Ok(Strategy::NextFit),
+            _ => Err(()),
         }
     }
 }
 
 fn main() {
-    println!("Answer: {}", process(INPUT));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => println!("Answer: {}", process(INPUT)),
+        [command, strategy] if command == "steps" => print_steps(
+            &load_blocks(INPUT),
+            strategy.parse().expect("unknown strategy"),
+        ),
+        [strategy, ..] => {
+            let strategy = strategy.parse().expect("unknown strategy");
+            println!("{}", Layout::run(&load_blocks(INPUT), strategy));
+        }
+    }
+}
+
+fn print_steps(units: &[DiskUnit], strategy: Strategy) {
+    let mut layout = Layout::from(units);
+    layout.compact(strategy);
+
+    println!("{}", render(units));
+    let mut blocks = to_blocks(units);
+    for m in &layout.history {
+        for i in 0..m.size {
+            blocks[m.to + i] = Some(m.id);
+            blocks[m.from + i] = None;
+        }
+        println!(
+            "move {} block(s) of file {} from {} to {}",
+            m.size, m.id, m.from, m.to
+        );
+        println!("{}", render_blocks(&blocks));
+    }
+
+    match to_disk_map(&layout.units()) {
+        Some(disk_map) => println!("Disk map: {}", disk_map),
+        None => println!("Disk map: spans larger than 9 blocks cannot be written back"),
+    }
 }
 
 fn process(input: &str) -> usize {
-    let mut blocks = load_blocks(input);
-    compact(&mut blocks);
+    let mut disk = Disk::from(load_blocks(input).as_slice());
+    disk.compact();
 
-    checksum(&blocks)
+    disk.checksum()
 }
 
 fn load_blocks(disk_map: &str) -> Vec<DiskUnit> {
@@ -65,110 +122,392 @@ fn load_blocks(disk_map: &str) -> Vec<DiskUnit> {
     blocks
 }
 
-fn compact(units: &mut Vec<DiskUnit>) {
-    let mut last: Option<usize> = None;
+// The dense format only stores sizes, alternating between files and free space,
+// so file ids are not kept: files are renumbered in disk order when the map is
+// loaded back. A disk starting with free space gets a zero-length file first.
+// `None` when a span is longer than 9 blocks, which one digit cannot hold.
+fn to_disk_map(units: &[DiskUnit]) -> Option<String> {
+    let mut disk_map = String::new();
+    let mut free: Option<usize> = None;
+
+    for u in units {
+        match *u {
+            DiskUnit::File { id: _, size } => {
+                if disk_map.is_empty() && free.is_some() {
+                    disk_map.push('0');
+                }
+                if !disk_map.is_empty() {
+                    disk_map.push(char::from_digit(free.unwrap_or(0) as u32, 10)?);
+                }
+                disk_map.push(char::from_digit(size as u32, 10)?);
+                free = None;
+            }
+            DiskUnit::Empty { size } => *free.get_or_insert(0) += size,
+        }
+    }
+    if let Some(size) = free {
+        if disk_map.is_empty() {
+            disk_map.push('0');
+        }
+        disk_map.push(char::from_digit(size as u32, 10)?);
+    }
+
+    Some(disk_map)
+}
+
+fn to_blocks(units: &[DiskUnit]) -> Vec<Option<usize>> {
+    units
+        .iter()
+        .flat_map(|u| vec![u.get_id(); u.get_size()])
+        .collect()
+}
+
+fn render_blocks(blocks: &[Option<usize>]) -> String {
+    blocks
+        .iter()
+        .map(|b| match b {
+            Some(id) => char::from_digit((id % 10) as u32, 10).unwrap(),
+            None => '.',
+        })
+        .collect()
+}
+
+fn render(units: &[DiskUnit]) -> String {
+    render_blocks(&to_blocks(units))
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct FileSpan {
+    id: usize,
+    start: usize,
+    size: usize,
+}
 
-    while let Some(next_index) = next_to_move(last, units) {
-        let next = units[next_index];
-        last = Some(next.get_id().unwrap());
+impl FileSpan {
+    fn checksum(&self) -> usize {
+        self.id * (self.start * self.size + self.size * self.size.saturating_sub(1) / 2)
+    }
+}
 
-        let mut to_insert: Option<(usize, DiskUnit)> = None;
-        let mut to_empty: Option<usize> = None;
+// Files indexed by id, and for every gap size a min-heap of the offsets of the
+// free spans of exactly that size. Gaps only separated by zero-length files are
+// one span, as in `Layout`.
+struct Disk {
+    files: Vec<FileSpan>,
+    free: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl From<&[DiskUnit]> for Disk {
+    fn from(units: &[DiskUnit]) -> Self {
+        let mut disk = Disk {
+            files: vec![],
+            free: vec![],
+        };
 
-        for (i, u) in units.iter_mut().enumerate() {
-            match u {
-                DiskUnit::File { id, size: _ } => {
-                    if *id == next.get_id().unwrap() {
-                        break;
+        let mut pos = 0;
+        let mut gap: Option<usize> = None;
+        for u in units {
+            match *u {
+                DiskUnit::File { id, size } => {
+                    if size > 0 {
+                        if let Some(start) = gap.take() {
+                            disk.add_free(start, pos - start);
+                        }
                     }
+                    disk.files.push(FileSpan {
+                        id,
+                        start: pos,
+                        size,
+                    });
                 }
-                DiskUnit::Empty { size } if *size > next.get_size() => {
-                    to_insert = Some((
-                        i + 1,
-                        DiskUnit::Empty {
-                            size: *size - next.get_size(),
-                        },
-                    ));
-                    to_empty = Some(next_index);
-                    *u = next;
-                    break;
+                DiskUnit::Empty { .. } => {
+                    gap.get_or_insert(pos);
                 }
-                DiskUnit::Empty { size } if *size == next.get_size() => {
-                    *u = next;
-                    to_empty = Some(next_index);
-                    break;
-                }
-                DiskUnit::Empty { size: _ } => {}
             }
+            pos += u.get_size();
+        }
+        if let Some(start) = gap {
+            disk.add_free(start, pos - start);
         }
 
-        if let Some(i) = to_empty {
-            let u = units.get_mut(i).unwrap();
-            u.empty();
+        disk
+    }
+}
+
+impl Disk {
+    fn add_free(&mut self, start: usize, size: usize) {
+        if size == 0 {
+            return;
         }
-        if let Some((i, u)) = to_insert {
-            units.insert(i, u);
+        if self.free.len() <= size {
+            self.free.resize_with(size + 1, BinaryHeap::new);
         }
-        *units = merge_empties(units);
+        self.free[size].push(Reverse(start));
+    }
+
+    fn compact(&mut self) {
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id];
+            if file.size == 0 {
+                continue;
+            }
+
+            let leftmost = (file.size..self.free.len())
+                .filter_map(|size| self.free[size].peek().map(|Reverse(start)| (*start, size)))
+                .filter(|(start, _)| *start < file.start)
+                .min();
+
+            if let Some((start, size)) = leftmost {
+                self.free[size].pop();
+                self.add_free(start + file.size, size - file.size);
+                self.files[id].start = start;
+            }
+        }
+    }
+
+    fn checksum(&self) -> usize {
+        self.files.iter().map(|f| f.checksum()).sum()
     }
 }
 
-fn merge_empties(units: &Vec<DiskUnit>) -> Vec<DiskUnit> {
-    let mut sum = 0;
-    let mut new = vec![];
-    for u in units {
-        match u {
-            DiskUnit::File { id: _, size: _ } => {
-                if sum > 0 {
-                    new.push(DiskUnit::Empty { size: sum });
-                    sum = 0;
-                }
-                new.push(*u);
+#[derive(Debug, PartialEq)]
+struct Report {
+    checksum: usize,
+    files_moved: usize,
+    blocks_moved: usize,
+    largest_free_span: usize,
+    fragmentation: f64,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "checksum: {}, files moved: {}, blocks moved: {}, largest free span: {}, fragmentation: {:.3}",
+            self.checksum,
+            self.files_moved,
+            self.blocks_moved,
+            self.largest_free_span,
+            self.fragmentation
+        )
+    }
+}
+
+// Slower but general model of the disk for comparing allocation strategies: file
+// fragments plus an ordered free list where adjacent spans are always merged.
+struct Layout {
+    files: Vec<FileSpan>,
+    free: BTreeMap<usize, usize>,
+    len: usize,
+    cursor: usize,
+    history: Vec<Move>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Move {
+    id: usize,
+    from: usize,
+    to: usize,
+    size: usize,
+}
+
+impl From<&[DiskUnit]> for Layout {
+    fn from(units: &[DiskUnit]) -> Self {
+        let mut layout = Layout {
+            files: vec![],
+            free: BTreeMap::new(),
+            len: 0,
+            cursor: 0,
+            history: vec![],
+        };
+
+        let mut pos = 0;
+        for u in units {
+            match *u {
+                DiskUnit::File { id, size } => layout.files.push(FileSpan {
+                    id,
+                    start: pos,
+                    size,
+                }),
+                DiskUnit::Empty { size } => layout.release(pos, size),
             }
-            DiskUnit::Empty { size } => sum += size,
+            pos += u.get_size();
         }
+        layout.len = pos;
+
+        layout
     }
-    new
 }
 
-fn next_to_move(last_id: Option<usize>, units: &[DiskUnit]) -> Option<usize> {
-    if last_id.is_some() && last_id.unwrap() == 0 {
-        return None;
+impl Layout {
+    fn run(units: &[DiskUnit], strategy: Strategy) -> Report {
+        let mut layout = Layout::from(units);
+        layout.compact(strategy);
+        layout.report()
     }
 
-    if last_id.is_none() {
-        return Some(units.len() - 1);
+    fn compact(&mut self, strategy: Strategy) {
+        match strategy {
+            Strategy::BlockByBlock => self.move_blocks(),
+            _ => self.move_files(strategy),
+        }
     }
 
-    units.iter().enumerate().find_map(|(i, u)| match u {
-        DiskUnit::File { id, size: _ } => {
-            if *id == last_id.unwrap() - 1 {
-                Some(i)
-            } else {
-                None
+    fn units(&self) -> Vec<DiskUnit> {
+        let mut files: Vec<&FileSpan> = self.files.iter().filter(|f| f.size > 0).collect();
+        files.sort_by_key(|f| f.start);
+
+        let mut units = vec![];
+        let mut pos = 0;
+        for f in files {
+            if f.start > pos {
+                units.push(DiskUnit::Empty {
+                    size: f.start - pos,
+                });
             }
+            units.push(DiskUnit::File {
+                id: f.id,
+                size: f.size,
+            });
+            pos = f.start + f.size;
+        }
+        if self.len > pos {
+            units.push(DiskUnit::Empty {
+                size: self.len - pos,
+            });
         }
-        DiskUnit::Empty { size: _ } => None,
-    })
-}
 
-fn checksum(blocks: &[DiskUnit]) -> usize {
-    let mut total = 0;
-    let mut pos = 0;
+        units
+    }
+
+    fn move_blocks(&mut self) {
+        let mut fragments = vec![];
+        for file in self.files.iter_mut().rev() {
+            while file.size > 0 {
+                let Some((&start, &span)) = self.free.first_key_value() else {
+                    break;
+                };
+                if start > file.start {
+                    break;
+                }
+
+                let size = span.min(file.size);
+                fragments.push(FileSpan {
+                    id: file.id,
+                    start,
+                    size,
+                });
+                file.size -= size;
+                self.history.push(Move {
+                    id: file.id,
+                    from: file.start + file.size,
+                    to: start,
+                    size,
+                });
 
-    for b in blocks {
-        match b {
-            DiskUnit::File { id, size } => {
-                for i in pos..pos + size {
-                    total += i * id;
+                self.free.remove(&start);
+                if span > size {
+                    self.free.insert(start + size, span - size);
                 }
-                pos += size;
+                Self::merge_free(&mut self.free, file.start + file.size, size);
+            }
+        }
+        self.files.append(&mut fragments);
+    }
+
+    fn move_files(&mut self, strategy: Strategy) {
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id];
+            if file.size == 0 {
+                continue;
+            }
+
+            if let Some((start, span)) = self.find_span(strategy, &file) {
+                self.free.remove(&start);
+                if span > file.size {
+                    self.free.insert(start + file.size, span - file.size);
+                }
+                self.release(file.start, file.size);
+
+                self.files[id].start = start;
+                self.history.push(Move {
+                    id: file.id,
+                    from: file.start,
+                    to: start,
+                    size: file.size,
+                });
+                self.cursor = start + file.size;
+            }
+        }
+    }
+
+    fn find_span(&self, strategy: Strategy, file: &FileSpan) -> Option<(usize, usize)> {
+        let mut candidates = self
+            .free
+            .range(..file.start)
+            .filter(|(_, size)| **size >= file.size)
+            .map(|(start, size)| (*start, *size));
+
+        match strategy {
+            Strategy::FirstFit => candidates.next(),
+            Strategy::BestFit => candidates.min_by_key(|(start, size)| (*size, *start)),
+            Strategy::WorstFit => candidates.min_by_key(|(start, size)| (Reverse(*size), *start)),
+            Strategy::NextFit => {
+                let candidates: Vec<_> = candidates.collect();
+                candidates
+                    .iter()
+                    .find(|(start, _)| *start >= self.cursor)
+                    .or(candidates.first())
+                    .copied()
+            }
+            Strategy::BlockByBlock => panic!("block-by-block does not place whole files"),
+        }
+    }
+
+    fn release(&mut self, start: usize, size: usize) {
+        Self::merge_free(&mut self.free, start, size);
+    }
+
+    fn merge_free(free: &mut BTreeMap<usize, usize>, mut start: usize, mut size: usize) {
+        if size == 0 {
+            return;
+        }
+
+        if let Some((&prev_start, &prev_size)) = free.range(..start).next_back() {
+            if prev_start + prev_size == start {
+                free.remove(&prev_start);
+                start = prev_start;
+                size += prev_size;
             }
-            DiskUnit::Empty { size } => pos += size,
         }
+        if let Some(next_size) = free.remove(&(start + size)) {
+            size += next_size;
+        }
+
+        free.insert(start, size);
     }
 
-    total
+    fn report(&self) -> Report {
+        let largest_free_span = self.free.values().copied().max().unwrap_or(0);
+        let total_free: usize = self.free.values().sum();
+
+        // Block by block moves a file in several fragments
+        let mut files_moved: Vec<usize> = self.history.iter().map(|m| m.id).collect();
+        files_moved.sort();
+        files_moved.dedup();
+
+        Report {
+            checksum: self.files.iter().map(|f| f.checksum()).sum(),
+            files_moved: files_moved.len(),
+            blocks_moved: self.history.iter().map(|m| m.size).sum(),
+            largest_free_span,
+            fragmentation: if total_free == 0 {
+                0.0
+            } else {
+                1.0 - largest_free_span as f64 / total_free as f64
+            },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -180,4 +519,96 @@ mod tests {
         let input = r#"2333133121414131402"#;
         assert_eq!(process(input), 2858)
     }
+
+    #[test]
+    fn whole_files() {
+        assert_eq!(process("12345"), 132);
+        assert_eq!(process("9953877292941"), 5768);
+        assert_eq!(process("35463146626"), 1325);
+        assert_eq!(process("101010101010101010101"), 385);
+    }
+
+    #[test]
+    fn strategies() {
+        let units = load_blocks("2333133121414131402");
+        assert_eq!(
+            Layout::run(&units, Strategy::BlockByBlock),
+            Report {
+                checksum: 1928,
+                files_moved: 4,
+                blocks_moved: 12,
+                largest_free_span: 14,
+                fragmentation: 0.0
+            }
+        );
+        let report = Layout::run(&units, Strategy::FirstFit);
+        assert_eq!(
+            (report.checksum, report.files_moved, report.blocks_moved),
+            (2858, 4, 8)
+        );
+        assert_eq!(report.largest_free_span, 5);
+
+        let checksum = |input, strategy| Layout::run(&load_blocks(input), strategy).checksum;
+        assert_eq!(checksum("1512121", Strategy::FirstFit), 10);
+        assert_eq!(checksum("1512121", Strategy::BestFit), 38);
+        assert_eq!(checksum("13151", Strategy::FirstFit), 4);
+        assert_eq!(checksum("13151", Strategy::WorstFit), 11);
+        assert_eq!(checksum("1113112", Strategy::FirstFit), 25);
+        assert_eq!(checksum("1113112", Strategy::NextFit), 32);
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = "2333133121414131402";
+        let units = load_blocks(input);
+        assert_eq!(to_disk_map(&units).unwrap(), input);
+        assert_eq!(render(&units), "00...111...2...333.44.5555.6666.777.888899");
+
+        let mut layout = Layout::from(units.as_slice());
+        assert_eq!(to_disk_map(&layout.units()).unwrap(), input);
+
+        layout.compact(Strategy::FirstFit);
+        assert_eq!(layout.history.len(), 4);
+        assert_eq!(
+            render(&layout.units()),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(
+            to_disk_map(&layout.units()).unwrap(),
+            "20201030312134414542"
+        );
+
+        let mut layout = Layout::from(units.as_slice());
+        layout.compact(Strategy::BlockByBlock);
+        assert_eq!(
+            render(&layout.units()),
+            "0099811188827773336446555566.............."
+        );
+        assert_eq!(to_disk_map(&layout.units()), None);
+    }
+
+    #[test]
+    fn leading_gap() {
+        let units = load_blocks("0312");
+        assert_eq!(to_disk_map(&units).unwrap(), "0312");
+
+        let layout = Layout::from(units.as_slice());
+        assert_eq!(render(&layout.units()), "...1..");
+        assert_eq!(to_disk_map(&layout.units()).unwrap(), "0312");
+
+        let layout = Layout::from(load_blocks("03").as_slice());
+        assert_eq!(to_disk_map(&layout.units()).unwrap(), "03");
+    }
+
+    #[test]
+    fn zero_length_files() {
+        let input = "3504925899139441177";
+        let report = Layout::run(&load_blocks(input), Strategy::FirstFit);
+        assert_eq!(process(input), report.checksum);
+        assert_eq!(process(input), 6088);
+
+        // The gaps around file 1 form a single span of 7 blocks that file 2 fits in
+        assert_eq!(process("23045"), 40);
+        assert_eq!(render(&load_blocks("23045")), "00.......22222");
+    }
 }
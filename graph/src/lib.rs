@@ -0,0 +1,214 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum GraphError {
+    InvalidEdge(String),
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::InvalidEdge(line) => write!(f, "expected `a-b`, found {:?}", line),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Undirected graph over named nodes, identified by their insertion order.
+#[derive(Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    neighbours: Vec<HashSet<usize>>,
+}
+
+/// Parses one `a-b` edge per line.
+impl FromStr for Graph {
+    type Err = GraphError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut graph = Graph::default();
+        for l in s.lines() {
+            match l.split_once('-') {
+                Some((a, b)) if !a.is_empty() && !b.is_empty() && !b.contains('-') => {
+                    graph.add_edge(a, b)
+                }
+                _ => return Err(GraphError::InvalidEdge(l.to_string())),
+            }
+        }
+        Ok(graph)
+    }
+}
+
+impl Graph {
+    pub fn add_node(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+
+        self.names.push(name.to_string());
+        self.neighbours.push(HashSet::new());
+        self.ids.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    /// Connects `a` and `b`, a node is never its own neighbour so `a-a` only
+    /// adds the node.
+    pub fn add_edge(&mut self, a: &str, b: &str) {
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        if a == b {
+            return;
+        }
+        self.neighbours[a].insert(b);
+        self.neighbours[b].insert(a);
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn neighbours(&self, id: usize) -> &HashSet<usize> {
+        &self.neighbours[id]
+    }
+
+    pub fn are_connected(&self, a: usize, b: usize) -> bool {
+        self.neighbours[a].contains(&b)
+    }
+
+    /// Every set of three nodes all connected to each other, each listed once
+    /// with its ids in increasing order, sorted.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut triangles = vec![];
+        for a in 0..self.len() {
+            for &b in self.neighbours[a].iter().filter(|&&b| b > a) {
+                for &c in self.neighbours[b].iter().filter(|&&c| c > b) {
+                    if self.are_connected(a, c) {
+                        triangles.push([a, b, c]);
+                    }
+                }
+            }
+        }
+        triangles.sort();
+        triangles
+    }
+
+    /// Largest set of nodes all connected to each other, found with
+    /// Bron–Kerbosch. Pivoting on the candidate with the most neighbours skips
+    /// the branches that could only find subsets of cliques already explored.
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        let mut best = vec![];
+        self.bron_kerbosch(
+            &mut vec![],
+            (0..self.len()).collect(),
+            HashSet::new(),
+            &mut best,
+        );
+        best
+    }
+
+    fn bron_kerbosch(
+        &self,
+        clique: &mut Vec<usize>,
+        mut candidates: HashSet<usize>,
+        mut excluded: HashSet<usize>,
+        best: &mut Vec<usize>,
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() && clique.len() > best.len() {
+                *best = clique.clone();
+            }
+            return;
+        }
+        if clique.len() + candidates.len() <= best.len() {
+            return;
+        }
+
+        let pivot = *candidates
+            .union(&excluded)
+            .max_by_key(|&&p| self.neighbours[p].intersection(&candidates).count())
+            .unwrap();
+
+        let branches: Vec<usize> = candidates
+            .difference(&self.neighbours[pivot])
+            .copied()
+            .collect();
+        for node in branches {
+            clique.push(node);
+            self.bron_kerbosch(
+                clique,
+                candidates
+                    .intersection(&self.neighbours[node])
+                    .copied()
+                    .collect(),
+                excluded
+                    .intersection(&self.neighbours[node])
+                    .copied()
+                    .collect(),
+                best,
+            );
+            clique.pop();
+
+            candidates.remove(&node);
+            excluded.insert(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let graph: Graph = "a-b\nb-c\na-b".parse().unwrap();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.name(2), "c");
+        assert!(graph.are_connected(graph.id("b").unwrap(), graph.id("a").unwrap()));
+        assert_eq!(graph.neighbours(1).len(), 2);
+
+        assert_eq!(
+            "a-b\nab".parse::<Graph>().err(),
+            Some(GraphError::InvalidEdge("ab".to_string()))
+        );
+        assert!("a-b-c".parse::<Graph>().is_err());
+    }
+
+    #[test]
+    fn triangles() {
+        let graph: Graph = "a-b\nb-c\nc-a\nc-d\nd-a".parse().unwrap();
+        assert_eq!(graph.triangles(), vec![[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn maximum_clique() {
+        let graph: Graph = "a-b\nb-c\nc-a\nc-d\nd-a\nb-d\nd-e".parse().unwrap();
+        let mut clique = graph.maximum_clique();
+        clique.sort();
+        assert_eq!(clique, vec![0, 1, 2, 3]);
+
+        assert!(Graph::default().maximum_clique().is_empty());
+
+        let graph: Graph = "a-a\na-b\nb-b\nb-c".parse().unwrap();
+        assert_eq!(graph.neighbours(0).len(), 1);
+        assert_eq!(graph.maximum_clique().len(), 2);
+        assert!(graph.triangles().is_empty());
+    }
+}